@@ -0,0 +1,134 @@
+//! Compares instruction decoding on the day 09 BOOST program in sensor boost
+//! mode: the original string-based decoder against the arithmetic one, and
+//! whole runs with the decoded instruction cache off and on.
+//!
+//!     cargo bench --bench decode
+
+use std::convert::TryFrom;
+use std::time::Instant;
+
+use intcode::hook::{Event, Hook};
+use intcode::{decode_instruction, Instruction, Memory, Parameter, ParameterMode, Program};
+
+struct Addresses(Vec<usize>);
+
+impl Hook for Addresses {
+    fn on_step(&mut self, event: &Event) {
+        self.0.push(event.i_ptr);
+    }
+}
+
+fn main() {
+    let image = intcode::parse::<i64>(include_str!("../../09/src/input")).unwrap();
+
+    // Every address the program executes an instruction at, in order. BOOST
+    // doesn't modify its own code, so decoding these against the initial
+    // image sees exactly what the VM saw.
+    let mut addresses = Addresses(vec![]);
+    let output = Program::init(image.clone()).execute_with(&[2], &mut addresses).unwrap();
+    let addresses = addresses.0;
+    let memory = Memory::dense(image.clone());
+    println!("{} instructions executed, output {:?}", addresses.len(), output);
+
+    for &i_ptr in &addresses {
+        assert_eq!(legacy_decode_instruction(&memory, i_ptr), decode_instruction(&memory, i_ptr).unwrap());
+    }
+
+    let legacy = time("decode, strings", 5, || {
+        addresses.iter().map(|&i_ptr| legacy_decode_instruction(&memory, i_ptr).size()).sum()
+    });
+    let arithmetic = time("decode, arithmetic", 5, || {
+        addresses.iter().map(|&i_ptr| decode_instruction(&memory, i_ptr).unwrap().size()).sum()
+    });
+    println!("speedup {:.1}x", legacy / arithmetic);
+
+    let uncached = time("run, no cache", 5, || {
+        let mut program = Program::init(image.clone());
+        program.set_decode_cache(false);
+        program.execute(&[2]).unwrap().len()
+    });
+    let cached = time("run, cache", 5, || {
+        Program::init(image.clone()).execute(&[2]).unwrap().len()
+    });
+    println!("speedup {:.1}x", uncached / cached);
+}
+
+/// Returns the mean seconds per run.
+fn time(name: &str, runs: u32, run: impl Fn() -> usize) -> f64 {
+    let start = Instant::now();
+    let mut check = 0;
+    for _ in 0..runs {
+        check += run();
+    }
+    let per_run = start.elapsed() / runs;
+    println!("{:<20} {:>10.3?} per run  ({})", name, per_run, check);
+    per_run.as_secs_f64()
+}
+
+/// The decoder as it was originally written for days 05, 07 and 09.
+fn legacy_decode_instruction(memory: &Memory, i_ptr: usize) -> Instruction {
+    let opcode = memory[i_ptr];
+    let opcode_bytes: Vec<u8> = opcode.to_string().as_bytes().iter().map(|b| b - 48).collect();
+    let p_ptr: i64 = opcode_bytes.len() as i64 - 3;
+    let inst = [if p_ptr <= -2 { 0 } else { opcode_bytes[(p_ptr + 1) as usize] }, opcode_bytes[(p_ptr + 2) as usize]];
+
+    use Instruction::*;
+    match inst {
+        [0, 1] => {
+            let mut params = get_params(memory, i_ptr, 3, p_ptr, &opcode_bytes);
+            Add(params.next().unwrap(), params.next().unwrap(), params.next().unwrap())
+        },
+        [0, 2] => {
+            let mut params = get_params(memory, i_ptr, 3, p_ptr, &opcode_bytes);
+            Mul(params.next().unwrap(), params.next().unwrap(), params.next().unwrap())
+        },
+        [0, 3] => {
+            let mut params = get_params(memory, i_ptr, 1, p_ptr, &opcode_bytes);
+            In(params.next().unwrap())
+        },
+        [0, 4] => {
+            let mut params = get_params(memory, i_ptr, 1, p_ptr, &opcode_bytes);
+            Out(params.next().unwrap())
+        },
+        [0, 5] => {
+            let mut params = get_params(memory, i_ptr, 2, p_ptr, &opcode_bytes);
+            Jnz(params.next().unwrap(), params.next().unwrap())
+        },
+        [0, 6] => {
+            let mut params = get_params(memory, i_ptr, 2, p_ptr, &opcode_bytes);
+            Jz(params.next().unwrap(), params.next().unwrap())
+        },
+        [0, 7] => {
+            let mut params = get_params(memory, i_ptr, 3, p_ptr, &opcode_bytes);
+            Lt(params.next().unwrap(), params.next().unwrap(), params.next().unwrap())
+        },
+        [0, 8] => {
+            let mut params = get_params(memory, i_ptr, 3, p_ptr, &opcode_bytes);
+            Eq(params.next().unwrap(), params.next().unwrap(), params.next().unwrap())
+        },
+        [0, 9] => {
+            let mut params = get_params(memory, i_ptr, 1, p_ptr, &opcode_bytes);
+            ChangeBase(params.next().unwrap())
+        },
+        [9, 9] => Halt,
+        _ => panic!("Unknown opcode!"),
+    }
+}
+
+fn get_params<'mem>(
+    memory: &'mem Memory,
+    i_ptr: usize,
+    n: usize,
+    p_ptr: i64,
+    opcode_bytes: &'mem [u8]
+) -> impl Iterator<Item=Parameter> + 'mem {
+    (0..n).map(move |i| {
+        let p_offset = p_ptr - i as i64;
+        let mode = if p_offset < 0 {
+            ParameterMode::Position
+        } else {
+            ParameterMode::try_from(opcode_bytes[p_offset as usize]).unwrap()
+        };
+        Parameter { mode, value: memory[1 + i_ptr + i] }
+    })
+}
@@ -0,0 +1,126 @@
+//! Runs day 07's amplifier feedback loop for every phase permutation under
+//! each way of scheduling machines, checking they agree and timing them.
+//!
+//!     cargo bench --bench schedulers
+
+use std::sync::mpsc;
+use std::time::Instant;
+
+use intcode::executor::{self, LocalExecutor};
+use intcode::network::Network;
+use intcode::{threaded, Program};
+
+fn main() {
+    let image = intcode::parse::<i64>(include_str!("../../07/src/input")).unwrap();
+    let permutations = permutations(vec![5, 6, 7, 8, 9]);
+
+    let cooperative = time("cooperative", || max(&permutations, |p| cooperative(&image, p)));
+    let threads = time("threads", || max(&permutations, |p| threads(&image, p)));
+    let futures = time("futures", || max(&permutations, |p| futures(&image, p)));
+
+    assert_eq!(cooperative, threads);
+    assert_eq!(cooperative, futures);
+}
+
+fn cooperative(image: &[i64], phases: &[i64]) -> i64 {
+    let mut network = Network::new();
+    for (i, &phase) in phases.iter().enumerate() {
+        let input = format!("amp{}", i);
+        let output = format!("amp{}", (i + 1) % phases.len());
+        network.add_node(&input, Program::init(image.to_vec()), &input, &[&output]);
+        network.send(&input, phase);
+    }
+    network.send("amp0", 0);
+
+    let report = network.run().unwrap();
+    *report.nodes.last().unwrap().outputs.last().unwrap()
+}
+
+/// The last amplifier writes back to this thread, which forwards each
+/// signal on to the first amplifier and remembers the last one.
+fn threads(image: &[i64], phases: &[i64]) -> i64 {
+    let (first_tx, mut rx) = mpsc::channel();
+    first_tx.send(phases[0]).unwrap();
+    first_tx.send(0).unwrap();
+
+    let mut handles = vec![];
+    for &phase in &phases[1..] {
+        let (tx, next_rx) = mpsc::channel();
+        tx.send(phase).unwrap();
+        handles.push(threaded::spawn(Program::init(image.to_vec()), rx, tx));
+        rx = next_rx;
+    }
+    let (tx, last_rx) = mpsc::channel();
+    handles.push(threaded::spawn(Program::init(image.to_vec()), rx, tx));
+
+    let mut signal = 0;
+    for value in last_rx {
+        signal = value;
+        let _ = first_tx.send(value);
+    }
+    for handle in handles {
+        handle.join().unwrap().unwrap();
+    }
+    signal
+}
+
+/// As with threads, the last amplifier writes to a forwarding task that
+/// passes each signal on to the first amplifier and remembers the last one.
+fn futures(image: &[i64], phases: &[i64]) -> i64 {
+    let mut executor = LocalExecutor::new();
+    let (first_tx, mut rx) = executor::channel();
+    first_tx.send(phases[0]);
+    first_tx.send(0);
+
+    for &phase in &phases[1..] {
+        let (tx, next_rx) = executor::channel();
+        tx.send(phase);
+        executor.spawn(executor::run(Program::init(image.to_vec()), rx, tx));
+        rx = next_rx;
+    }
+    let (tx, mut last_rx) = executor::channel();
+    executor.spawn(executor::run(Program::init(image.to_vec()), rx, tx));
+
+    let signal = executor.spawn(async move {
+        let mut signal = 0;
+        while last_rx.ready().await {
+            signal = last_rx.try_recv().unwrap();
+            first_tx.send(signal);
+        }
+        signal
+    });
+
+    assert_eq!(executor.run(), 0);
+    signal.take().unwrap()
+}
+
+fn max(permutations: &[Vec<i64>], run: impl Fn(&[i64]) -> i64) -> i64 {
+    permutations.iter().map(|p| run(p)).max().unwrap()
+}
+
+fn time(name: &str, run: impl Fn() -> i64) -> i64 {
+    let runs = 20;
+    let start = Instant::now();
+    let mut result = 0;
+    for _ in 0..runs {
+        result = run();
+    }
+    println!("{:<12} {:>10.3?} per run  (answer {})", name, start.elapsed() / runs, result);
+    result
+}
+
+fn permutations(items: Vec<i64>) -> Vec<Vec<i64>> {
+    if items.len() <= 1 {
+        return vec![items];
+    }
+    let mut out = vec![];
+    for i in 0..items.len() {
+        let mut rest = items.clone();
+        let first = rest.remove(i);
+        for mut tail in permutations(rest) {
+            tail.insert(0, first);
+            out.push(tail);
+        }
+    }
+    out
+}
@@ -0,0 +1,220 @@
+//! Ahead-of-time translation of Intcode images to Rust source.
+//!
+//! `translate` takes the basic blocks of the program's control-flow graph
+//! and emits a module with one function, `run`, that dispatches on the
+//! address of the block to execute next. `run` has the same contract as
+//! `Program::run_io` and works on a `Program`'s own state, so a translated
+//! program can be paused, resumed, snapshotted or cross-checked against the
+//! interpreter like any other.
+//!
+//! Anything the translation can't vouch for is handed to the interpreter
+//! for the rest of the call: a jump to an address that doesn't start a
+//! block, a write to a word of translated code, a program whose code no
+//! longer matches the image it was translated from, or one running under a
+//! budget or with loop detection on, which only the interpreter enforces.
+//!
+//! The usual way in is a build script:
+//!
+//! ```ignore
+//! // build.rs
+//! let image = intcode_image();
+//! let source = intcode::aot::translate(&image, "boost");
+//! fs::write(Path::new(&env::var("OUT_DIR").unwrap()).join("boost.rs"), source).unwrap();
+//!
+//! // main.rs
+//! include!(concat!(env!("OUT_DIR"), "/boost.rs"));
+//! boost::run(&mut program, &mut input, &mut output)?;
+//! ```
+
+use std::fmt::Write;
+
+use crate::cfg::{Cfg, RegionKind};
+use crate::{Instruction, Memory, Parameter, ParameterMode};
+
+/// The translated words of an image as `(start, words)` runs, sorted by
+/// start. Translated code uses it to notice self-modification.
+#[derive(Debug, Clone, Copy)]
+pub struct Code(pub &'static [(usize, &'static [i64])]);
+
+impl Code {
+    /// Whether `address` holds a word of translated code.
+    pub fn contains(&self, address: usize) -> bool {
+        match self.0.binary_search_by_key(&address, |&(start, _)| start) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(i) => {
+                let (start, words) = self.0[i - 1];
+                address < start + words.len()
+            },
+        }
+    }
+
+    /// Whether every word of translated code is unchanged in `memory`.
+    pub fn matches(&self, memory: &Memory) -> bool {
+        self.0.iter().all(|&(start, words)| {
+            words.iter().enumerate().all(|(i, &word)| memory[start + i] == word)
+        })
+    }
+}
+
+/// Translates `image` into the source of a module called `name` holding
+/// `pub fn run(program, input, output)`.
+pub fn translate(image: &[i64], name: &str) -> String {
+    let cfg = Cfg::build(image);
+    let code = cfg.regions.iter()
+        .filter(|region| region.kind == RegionKind::Code)
+        .collect::<Vec<_>>();
+    let in_code = |address: usize| code.iter().any(|region| region.start <= address && address < region.end);
+
+    let mut out = String::new();
+    writeln!(out, "/// Translated from a {}-word Intcode image by `intcode::aot`.", image.len()).unwrap();
+    writeln!(out, "#[allow(clippy::all, unreachable_code, unreachable_patterns, unused_mut, unused_parens)]").unwrap();
+    writeln!(out, "pub mod {} {{", name).unwrap();
+    writeln!(out, "    use intcode::aot::Code;").unwrap();
+    writeln!(out, "    use intcode::{{Input, IntcodeError, Output, Program, StepResult}};").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    pub const CODE: Code = Code(&[").unwrap();
+    for region in &code {
+        let words = image[region.start..region.end].iter().map(i64::to_string).collect::<Vec<_>>().join(", ");
+        writeln!(out, "        ({}, &[{}]),", region.start, words).unwrap();
+    }
+    writeln!(out, "    ]);").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    pub fn run(").unwrap();
+    writeln!(out, "        program: &mut Program,").unwrap();
+    writeln!(out, "        input: &mut impl Input,").unwrap();
+    writeln!(out, "        output: &mut impl Output,").unwrap();
+    writeln!(out, "    ) -> Result<StepResult, IntcodeError> {{").unwrap();
+    writeln!(out, "        if !CODE.matches(&program.memory) || program.is_limited() {{").unwrap();
+    writeln!(out, "            return program.run_io(input, output);").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "        let mut ip = program.i_ptr;").unwrap();
+    writeln!(out, "        loop {{").unwrap();
+    writeln!(out, "            match ip {{").unwrap();
+
+    // Every `In` also starts an arm of its own, since that's where a
+    // program waiting on input resumes.
+    for block in cfg.blocks.values() {
+        writeln!(out, "                {} => {{", block.start).unwrap();
+        for (i, (address, instruction)) in block.instructions.iter().enumerate() {
+            if i > 0 && matches!(instruction, Instruction::In(_)) {
+                writeln!(out, "                    ip = {};", address).unwrap();
+                writeln!(out, "                }},").unwrap();
+                writeln!(out, "                {} => {{", address).unwrap();
+            }
+            emit(&mut out, *address, instruction, &in_code);
+        }
+        if !block.edges.is_empty() {
+            writeln!(out, "                    ip = {};", block.end).unwrap();
+        }
+        writeln!(out, "                }},").unwrap();
+    }
+
+    writeln!(out, "                _ => {{").unwrap();
+    writeln!(out, "                    program.i_ptr = ip;").unwrap();
+    writeln!(out, "                    return program.run_io(input, output);").unwrap();
+    writeln!(out, "                }},").unwrap();
+    writeln!(out, "            }}").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// Emits one instruction, leaving `program.i_ptr` on it so that errors
+/// and falling back to the interpreter see the same state they would have
+/// in `Program::step`.
+fn emit(out: &mut String, address: usize, instruction: &Instruction, in_code: &impl Fn(usize) -> bool) {
+    const INDENT: &str = "                    ";
+    writeln!(out, "{}// {:04}: {}", INDENT, address, instruction).unwrap();
+    writeln!(out, "{}program.i_ptr = {};", INDENT, address).unwrap();
+
+    let next = address + instruction.size();
+    let mut line = |text: String| writeln!(out, "{}{}", INDENT, text).unwrap();
+
+    if let Some(Parameter { mode: ParameterMode::Immediate, .. }) = instruction.write_parameter() {
+        line(format!("return Err(IntcodeError::WriteToImmediate {{ addr: {} }});", address));
+        return;
+    }
+
+    use Instruction::*;
+    match instruction {
+        Halt => {
+            line("program.halted = true;".to_string());
+            line("return Ok(StepResult::Halted);".to_string());
+        },
+        Add(p1, p2, p3) | Mul(p1, p2, p3) | Lt(p1, p2, p3) | Eq(p1, p2, p3) => {
+            line(format!("let store = {};", store(p3)));
+            let (a, b) = (read(p1), read(p2));
+            line(match instruction {
+                Add(..) => format!("let value = program.add({}, {})?;", a, b),
+                Mul(..) => format!("let value = program.mul({}, {})?;", a, b),
+                Lt(..) => format!("let value = ({} < {}) as i64;", a, b),
+                _ => format!("let value = ({} == {}) as i64;", a, b),
+            });
+            line("program.write(store, value)?;".to_string());
+            check_write(&mut line, p3, next, in_code);
+        },
+        In(p1) => {
+            line(format!("let store = {};", store(p1)));
+            line("program.check_limit(store)?;".to_string());
+            line("let value = match input.next_input() {".to_string());
+            line("    Some(value) => value,".to_string());
+            line("    None => return Ok(StepResult::NeedsInput),".to_string());
+            line("};".to_string());
+            line("program.write(store, value)?;".to_string());
+            check_write(&mut line, p1, next, in_code);
+        },
+        Out(p1) => {
+            line(format!("output.output({});", read(p1)));
+        },
+        Jnz(p1, p2) | Jz(p1, p2) => {
+            line(format!("let (test, target) = ({}, {});", read(p1), read(p2)));
+            line(format!("if test {} 0 {{", if let Jnz(..) = instruction { "!=" } else { "==" }));
+            line("    ip = program.address(target)?;".to_string());
+            line("    continue;".to_string());
+            line("}".to_string());
+        },
+        ChangeBase(p1) => {
+            line(format!("program.relative_base = program.add(program.relative_base, {})?;", read(p1)));
+        },
+        Custom { .. } => {
+            line("return program.run_io(input, output);".to_string());
+        },
+    }
+}
+
+/// Hands over to the interpreter after a write that hit, or might have
+/// hit, translated code.
+fn check_write(line: &mut impl FnMut(String), parameter: &Parameter, next: usize, in_code: &impl Fn(usize) -> bool) {
+    let exit = format!("program.i_ptr = {}; return program.run_io(input, output);", next);
+    match parameter.mode {
+        ParameterMode::Position if parameter.value >= 0 && in_code(parameter.value as usize) => {
+            line(format!("{{ {} }}", exit));
+        },
+        ParameterMode::Relative => line(format!("if CODE.contains(store) {{ {} }}", exit)),
+        _ => {},
+    }
+}
+
+/// The expression for the value a parameter reads.
+fn read(parameter: &Parameter) -> String {
+    match parameter.mode {
+        ParameterMode::Immediate => format!("({})", parameter.value),
+        ParameterMode::Position if parameter.value >= 0 => format!("program.read({})?", parameter.value),
+        ParameterMode::Position => format!("program.read(program.address({})?)?", parameter.value),
+        ParameterMode::Relative => format!("program.read(program.address(program.add(program.relative_base, {})?)?)?", parameter.value),
+    }
+}
+
+/// The expression for the address a parameter writes to. Immediate
+/// parameters are turned away before this is called.
+fn store(parameter: &Parameter) -> String {
+    match parameter.mode {
+        ParameterMode::Immediate => unreachable!(),
+        ParameterMode::Position if parameter.value >= 0 => format!("{}", parameter.value),
+        ParameterMode::Position => format!("program.address({})?", parameter.value),
+        ParameterMode::Relative => format!("program.address(program.add(program.relative_base, {})?)?", parameter.value),
+    }
+}
@@ -0,0 +1,342 @@
+//! A two-pass assembler for Intcode.
+//!
+//! The syntax is the one the disassembler prints:
+//!
+//! ```text
+//! ; Doubles every input until it reads a zero.
+//! loop:   in @x
+//!         jz @x, #done
+//!         mul @x, #2, @x
+//!         out @x
+//!         jnz #1, #loop
+//! done:   hlt
+//! x:      .data 0
+//! ```
+//!
+//! Operands are `#n` (immediate), `@n` or a bare `n` (position) and `[rb+n]`
+//! (relative). Values may be integers, labels, or `label+n`/`label-n`. A
+//! numeric label such as `0012:` asserts the current address instead of
+//! defining a name, so disassembler listings assemble back into the same
+//! image. `data` is accepted as an alias for `.data`.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::ParameterMode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for AsmError {}
+
+/// Assembles `source` into a program image for `Program::init`.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut items = vec![];
+    let mut address = 0;
+
+    // First pass: parse every line, lay out addresses and collect labels.
+    for (i, text) in source.lines().enumerate() {
+        let mut line = Cursor { text, pos: 0, line: i + 1 };
+        line.text = &text[..text.find(';').unwrap_or(text.len())];
+
+        while let Some(label) = line.label() {
+            let column = line.column();
+            line.pos += label.len() + 1;
+            if label.starts_with(|c: char| c.is_ascii_digit()) {
+                let expected = label.parse::<usize>().map_err(|_| line.error_at(column, "bad address"))?;
+                if expected != address {
+                    return Err(line.error_at(column, &format!("address is {}, not {}", address, expected)));
+                }
+            } else if labels.insert(label.to_string(), address as i64).is_some() {
+                return Err(line.error_at(column, &format!("duplicate label {}", label)));
+            }
+        }
+
+        line.skip_space();
+        if line.at_end() {
+            continue;
+        }
+        let item = line.item()?;
+        address += item.size();
+        items.push(item);
+    }
+
+    // Second pass: resolve labels and encode.
+    let mut image = vec![];
+    for item in items {
+        match item {
+            Item::Data(values) => {
+                for value in values {
+                    image.push(value.resolve(&labels)?);
+                }
+            },
+            Item::Instruction { opcode, operands } => {
+                let mut word = opcode;
+                let mut scale = 100;
+                for operand in &operands {
+                    word += scale * match operand.mode {
+                        ParameterMode::Position => 0,
+                        ParameterMode::Immediate => 1,
+                        ParameterMode::Relative => 2,
+                    };
+                    scale *= 10;
+                }
+                image.push(word);
+                for operand in operands {
+                    image.push(operand.value.resolve(&labels)?);
+                }
+            },
+        }
+    }
+
+    Ok(image)
+}
+
+/// Opcode, number of operands and the index of the operand written to.
+fn lookup(mnemonic: &str) -> Option<(i64, usize, Option<usize>)> {
+    match mnemonic.to_ascii_lowercase().as_str() {
+        "add" => Some((1, 3, Some(2))),
+        "mul" => Some((2, 3, Some(2))),
+        "in" => Some((3, 1, Some(0))),
+        "out" => Some((4, 1, None)),
+        "jnz" => Some((5, 2, None)),
+        "jz" => Some((6, 2, None)),
+        "lt" => Some((7, 3, Some(2))),
+        "eq" => Some((8, 3, Some(2))),
+        "arb" => Some((9, 1, None)),
+        "hlt" => Some((99, 0, None)),
+        _ => None,
+    }
+}
+
+enum Item {
+    Instruction { opcode: i64, operands: Vec<Operand> },
+    Data(Vec<Value>),
+}
+
+impl Item {
+    fn size(&self) -> usize {
+        match self {
+            Item::Instruction { operands, .. } => 1 + operands.len(),
+            Item::Data(values) => values.len(),
+        }
+    }
+}
+
+struct Operand {
+    mode: ParameterMode,
+    value: Value,
+}
+
+struct Value {
+    line: usize,
+    column: usize,
+    label: Option<String>,
+    offset: i64,
+}
+
+impl Value {
+    fn resolve(&self, labels: &HashMap<String, i64>) -> Result<i64, AsmError> {
+        match &self.label {
+            None => Ok(self.offset),
+            Some(label) => match labels.get(label) {
+                Some(address) => address.checked_add(self.offset).ok_or_else(|| AsmError {
+                    line: self.line,
+                    column: self.column,
+                    message: format!("address {}{:+} out of range", label, self.offset),
+                }),
+                None => Err(AsmError {
+                    line: self.line,
+                    column: self.column,
+                    message: format!("undefined label {}", label),
+                }),
+            },
+        }
+    }
+}
+
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn column(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn at_end(&self) -> bool {
+        self.rest().trim().is_empty()
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn error_at(&self, column: usize, message: &str) -> AsmError {
+        AsmError { line: self.line, column, message: message.to_string() }
+    }
+
+    fn error(&self, message: &str) -> AsmError {
+        self.error_at(self.column(), message)
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.skip_space();
+        let rest = self.rest();
+        let end = rest.find(|c: char| !is_ident(c)).unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    /// Returns the label at the cursor, if the next word is followed by `:`.
+    fn label(&mut self) -> Option<&'a str> {
+        self.skip_space();
+        let rest = self.rest();
+        let end = rest.find(|c: char| !is_ident(c)).unwrap_or(rest.len());
+        if end > 0 && rest[end..].starts_with(':') {
+            Some(&rest[..end])
+        } else {
+            None
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, AsmError> {
+        let column = self.column();
+        let mnemonic = self.word();
+        if mnemonic.is_empty() {
+            return Err(self.error("expected a mnemonic"));
+        }
+
+        if mnemonic.eq_ignore_ascii_case(".data") || mnemonic.eq_ignore_ascii_case("data") {
+            let values = self.list()?
+                .into_iter()
+                .map(|(column, text)| self.value(column, text))
+                .collect::<Result<Vec<_>, _>>()?;
+            if values.is_empty() {
+                return Err(self.error_at(column, "expected at least one value"));
+            }
+            return Ok(Item::Data(values));
+        }
+
+        let (opcode, arity, write) = lookup(mnemonic)
+            .ok_or_else(|| self.error_at(column, &format!("unknown mnemonic {}", mnemonic)))?;
+        let operands = self.list()?;
+        if operands.len() != arity {
+            let message = format!("{} takes {} operands, got {}", mnemonic, arity, operands.len());
+            return Err(self.error_at(column, &message));
+        }
+
+        let operands = operands.into_iter()
+            .enumerate()
+            .map(|(i, (column, text))| {
+                let operand = self.operand(column, text)?;
+                if Some(i) == write && operand.mode == ParameterMode::Immediate {
+                    return Err(self.error_at(column, "cannot write to an immediate operand"));
+                }
+                Ok(operand)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Item::Instruction { opcode, operands })
+    }
+
+    /// Splits the rest of the line on commas, returning each trimmed piece
+    /// along with its column.
+    fn list(&mut self) -> Result<Vec<(usize, &'a str)>, AsmError> {
+        self.skip_space();
+        if self.at_end() {
+            return Ok(vec![]);
+        }
+
+        let mut pieces = vec![];
+        let mut start = self.pos;
+        for piece in self.rest().split(',') {
+            let leading = piece.len() - piece.trim_start().len();
+            let trimmed = piece.trim();
+            if trimmed.is_empty() {
+                return Err(self.error_at(start + leading + 1, "expected an operand"));
+            }
+            pieces.push((start + leading + 1, trimmed));
+            start += piece.len() + 1;
+        }
+        self.pos = self.text.len();
+
+        Ok(pieces)
+    }
+
+    fn operand(&self, column: usize, text: &str) -> Result<Operand, AsmError> {
+        if let Some(rest) = text.strip_prefix('#') {
+            Ok(Operand { mode: ParameterMode::Immediate, value: self.value(column + 1, rest)? })
+        } else if let Some(rest) = text.strip_prefix('@') {
+            Ok(Operand { mode: ParameterMode::Position, value: self.value(column + 1, rest)? })
+        } else if let Some(rest) = text.strip_prefix('[') {
+            let inner = rest.strip_suffix(']').ok_or_else(|| self.error_at(column, "missing ]"))?;
+            let offset = inner.trim_start().strip_prefix("rb")
+                .ok_or_else(|| self.error_at(column + 1, "expected rb"))?
+                .trim();
+            let value = if offset.is_empty() {
+                Value { line: self.line, column, label: None, offset: 0 }
+            } else if offset.starts_with('+') || offset.starts_with('-') {
+                let column = column + 1 + inner.find(offset).unwrap_or(0);
+                let mut value = self.value(column + 1, offset[1..].trim())?;
+                if offset.starts_with('-') {
+                    if value.label.is_some() {
+                        return Err(self.error_at(column, "cannot negate a label"));
+                    }
+                    value.offset = -value.offset;
+                }
+                value
+            } else {
+                return Err(self.error_at(column + 1, "expected rb+n or rb-n"));
+            };
+            Ok(Operand { mode: ParameterMode::Relative, value })
+        } else {
+            Ok(Operand { mode: ParameterMode::Position, value: self.value(column, text)? })
+        }
+    }
+
+    /// Parses an integer, `label`, `label+n` or `label-n`.
+    fn value(&self, column: usize, text: &str) -> Result<Value, AsmError> {
+        if let Ok(offset) = text.parse::<i64>() {
+            return Ok(Value { line: self.line, column, label: None, offset });
+        }
+
+        let end = text.find(|c: char| !is_ident(c)).unwrap_or(text.len());
+        let (label, rest) = text.split_at(end);
+        if label.is_empty() || label.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error_at(column, &format!("expected a number or label, got {:?}", text)));
+        }
+
+        let offset = if rest.is_empty() {
+            0
+        } else {
+            let parsed = rest.strip_prefix('+').unwrap_or(rest).parse::<i64>();
+            parsed.map_err(|_| self.error_at(column + end, &format!("bad offset {:?}", rest)))?
+        };
+
+        Ok(Value { line: self.line, column, label: Some(label.to_string()), offset })
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
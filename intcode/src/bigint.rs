@@ -0,0 +1,228 @@
+//! A minimal arbitrary-precision integer, just enough to be an Intcode
+//! word: addition, multiplication, comparison and decimal conversion.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Word;
+
+/// A signed integer of any size, stored as sign and magnitude with 32-bit
+/// limbs, least significant first. Zero has no limbs and is never negative.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { negative: negative && !limbs.is_empty(), limbs }
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let limb = u64::from(*a.get(i).unwrap_or(&0)) + u64::from(*b.get(i).unwrap_or(&0)) + carry;
+        sum.push(limb as u32);
+        carry = limb >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// `a - b`, where `a >= b`.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &x) in a.iter().enumerate() {
+        let limb = i64::from(x) - i64::from(*b.get(i).unwrap_or(&0)) - borrow;
+        borrow = (limb < 0) as i64;
+        difference.push((limb + (borrow << 32)) as u32);
+    }
+    difference
+}
+
+fn mul_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, &y) in b.iter().enumerate() {
+            let limb = u64::from(x) * u64::from(y) + u64::from(product[i + j]) + carry;
+            product[i + j] = limb as u32;
+            carry = limb >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+impl Word for BigInt {
+    fn from_i64(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 2 {
+            return None;
+        }
+        let magnitude = self.limbs.iter().rev().fold(0u64, |value, &limb| value << 32 | u64::from(limb));
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self.wrapping_add(other))
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self.wrapping_mul(other))
+    }
+
+    /// Never wraps.
+    fn wrapping_add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+        match compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitudes(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+
+    /// Never wraps.
+    fn wrapping_mul(&self, other: &Self) -> Self {
+        BigInt::new(self.negative != other.negative, mul_magnitudes(&self.limbs, &other.limbs))
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        BigInt::from_i64(value)
+    }
+}
+
+/// Decimal digits per chunk when converting to and from text.
+const CHUNK_DIGITS: usize = 9;
+const CHUNK: u32 = 1_000_000_000;
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off base-10^9 chunks, least significant first.
+        let mut chunks = vec![];
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            let mut remainder = 0u64;
+            for limb in limbs.iter_mut().rev() {
+                let value = remainder << 32 | u64::from(*limb);
+                *limb = (value / u64::from(CHUNK)) as u32;
+                remainder = value % u64::from(CHUNK);
+            }
+            chunks.push(remainder as u32);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+
+        let mut digits = String::new();
+        match chunks.split_last() {
+            None => digits.push('0'),
+            Some((most, rest)) => {
+                digits.push_str(&most.to_string());
+                for chunk in rest.iter().rev() {
+                    digits.push_str(&format!("{:09}", chunk));
+                }
+            },
+        }
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseBigIntError;
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid digit found in string")
+    }
+}
+
+impl Error for ParseBigIntError {}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    fn from_str(text: &str) -> Result<Self, ParseBigIntError> {
+        let (negative, digits) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ParseBigIntError);
+        }
+
+        let mut limbs: Vec<u32> = vec![];
+        let first = match digits.len() % CHUNK_DIGITS {
+            0 => CHUNK_DIGITS,
+            first => first,
+        };
+        let mut start = 0;
+        let mut end = first;
+        while start < digits.len() {
+            let chunk = digits[start..end].parse::<u32>().map_err(|_| ParseBigIntError)?;
+            let scale = 10u64.pow((end - start) as u32);
+            let mut carry = u64::from(chunk);
+            for limb in limbs.iter_mut() {
+                let value = u64::from(*limb) * scale + carry;
+                *limb = value as u32;
+                carry = value >> 32;
+            }
+            if carry > 0 {
+                limbs.push(carry as u32);
+            }
+            start = end;
+            end += CHUNK_DIGITS;
+        }
+
+        Ok(BigInt::new(negative, limbs))
+    }
+}
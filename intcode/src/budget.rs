@@ -0,0 +1,105 @@
+//! Limits on how long a program may run, and spotting programs that will
+//! never stop.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::Instant;
+
+use crate::hook::Effects;
+use crate::memory::Version;
+use crate::Word;
+
+/// Instructions between looks at the clock.
+pub(crate) const DEADLINE_INTERVAL: u32 = 1024;
+
+/// Limits on a run. Running out stops the machine with
+/// `IntcodeError::BudgetExceeded` before the next instruction, leaving it
+/// ready to carry on once the limit is raised.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    /// Instructions left to execute.
+    pub steps: Option<u64>,
+    /// When to stop. The clock is only read every `DEADLINE_INTERVAL`
+    /// instructions, so a run can go slightly over.
+    pub deadline: Option<Instant>,
+}
+
+/// Notices when a program comes back to a state it was already in without
+/// doing any I/O since: it is deterministic, so it will go round forever.
+///
+/// The state is the instruction pointer, the relative base and every word
+/// written since the last I/O; the rest of memory is as it was then. One
+/// state is remembered at a time, moved forward at powers of two (Brent's
+/// algorithm), so a loop is caught within a few times its length.
+#[derive(Debug, Clone)]
+pub(crate) struct LoopDetector<W> {
+    /// The memory as last seen, to notice changes from outside.
+    version: Option<Version>,
+    dirty: HashMap<usize, W>,
+    /// A hash of `dirty` kept up to date write by write, so that most
+    /// states can be told apart without comparing the maps.
+    hash: u64,
+    saved: Option<State<W>>,
+    power: u64,
+    length: u64,
+}
+
+#[derive(Debug, Clone)]
+struct State<W> {
+    i_ptr: usize,
+    relative_base: W,
+    hash: u64,
+    dirty: HashMap<usize, W>,
+}
+
+impl<W: Word> LoopDetector<W> {
+    pub fn new() -> Self {
+        Self { version: None, dirty: HashMap::new(), hash: 0, saved: None, power: 1, length: 0 }
+    }
+
+    /// Forgets everything seen so far.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Whether the machine is about to run from a state it was in before.
+    pub fn repeats(&mut self, i_ptr: usize, relative_base: &W, version: Version) -> bool {
+        if self.version != Some(version) {
+            self.reset();
+            self.version = Some(version);
+        }
+
+        if let Some(saved) = &self.saved {
+            if saved.i_ptr == i_ptr && saved.hash == self.hash && saved.relative_base == *relative_base && saved.dirty == self.dirty {
+                return true;
+            }
+        }
+        if self.saved.is_none() || self.length == self.power {
+            self.saved = Some(State { i_ptr, relative_base: relative_base.clone(), hash: self.hash, dirty: self.dirty.clone() });
+            self.power *= 2;
+            self.length = 0;
+        }
+        self.length += 1;
+        false
+    }
+
+    /// Takes note of what the instruction just checked by `repeats` did.
+    pub fn record(&mut self, effects: &Effects<W>, version: Version) {
+        if effects.input.is_some() || effects.output.is_some() {
+            self.reset();
+        } else if let Some(write) = &effects.write {
+            if let Some(old) = self.dirty.insert(write.address, write.new.clone()) {
+                self.hash = self.hash.wrapping_sub(entry_hash(write.address, &old));
+            }
+            self.hash = self.hash.wrapping_add(entry_hash(write.address, &write.new));
+        }
+        self.version = Some(version);
+    }
+}
+
+fn entry_hash<W: Hash>(address: usize, value: &W) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (address, value).hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,88 @@
+use crate::memory::Version;
+use crate::opcodes::Opcodes;
+use crate::{decode_instruction, Instruction, IntcodeError, Memory, Word};
+
+/// Instructions are only cached below this address, so a jump into the far
+/// reaches of sparse memory doesn't allocate a huge table.
+const MAX_CACHED: usize = 1 << 20;
+
+/// Instructions already decoded, by address, for one particular memory.
+///
+/// The cache remembers the `Version` of the memory it was filled from. The
+/// program's own writes go through `invalidate`, which drops just the
+/// instructions covering the written address; any other change to memory
+/// (a write from outside, swapping in another memory) shows up as a version
+/// mismatch and empties the whole cache.
+#[derive(Debug)]
+pub(crate) struct DecodeCache<W = i64> {
+    pub enabled: bool,
+    version: Option<Version>,
+    entries: Vec<Option<Instruction<W>>>,
+}
+
+impl<W: Word> DecodeCache<W> {
+    pub fn new() -> Self {
+        Self { enabled: true, version: None, entries: vec![] }
+    }
+
+    /// Decodes in the standard instruction set, or in `opcodes` if given.
+    pub fn decode(
+        &mut self,
+        memory: &Memory<W>,
+        i_ptr: usize,
+        opcodes: Option<&Opcodes<W>>,
+    ) -> Result<Instruction<W>, IntcodeError<W>> {
+        let decode = || match opcodes {
+            Some(opcodes) => opcodes.decode(memory, i_ptr),
+            None => decode_instruction(memory, i_ptr),
+        };
+        if !self.enabled || i_ptr >= memory.len().min(MAX_CACHED) {
+            return decode();
+        }
+
+        if self.version != Some(memory.version()) {
+            self.entries.clear();
+            self.version = Some(memory.version());
+        }
+        if let Some(Some(instruction)) = self.entries.get(i_ptr) {
+            return Ok(instruction.clone());
+        }
+
+        let instruction = decode()?;
+        if i_ptr >= self.entries.len() {
+            self.entries.resize(i_ptr + 1, None);
+        }
+        self.entries[i_ptr] = Some(instruction.clone());
+        Ok(instruction)
+    }
+
+    /// Forgets everything, e.g. after a change of instruction set.
+    pub fn clear(&mut self) {
+        self.version = None;
+        self.entries.clear();
+    }
+
+    /// Called right after the program writes `address` in `memory`.
+    pub fn invalidate(&mut self, memory: &Memory<W>, address: usize) {
+        let version = memory.version();
+        match self.version {
+            Some(cached) if cached.id == version.id && cached.writes + 1 == version.writes => {
+                self.version = Some(version);
+                // An instruction is at most four words long.
+                for start in address.saturating_sub(3)..=address {
+                    if let Some(entry) = self.entries.get_mut(start) {
+                        *entry = None;
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+impl<W> Clone for DecodeCache<W> {
+    /// A clone starts out empty, keeping forks of a program cheap.
+    fn clone(&self) -> Self {
+        Self { enabled: self.enabled, version: None, entries: vec![] }
+    }
+}
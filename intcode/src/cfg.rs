@@ -0,0 +1,268 @@
+//! Control-flow graphs of Intcode programs.
+//!
+//! The graph is built statically from address 0. Basic blocks end at jumps,
+//! at halts and just before jump targets. A `JNZ`/`JZ` with an immediate
+//! target gets an edge to it, and one with a computed target gets a
+//! `Computed` edge, since where it lands is only known at run time. Jumps
+//! with an immediate condition only get the edge they can actually take.
+//!
+//! Words no edge reaches are split into dead code, which decodes as a run of
+//! instructions that halts, jumps or runs into reachable code, and data.
+//! Code behind computed jumps, like the amplifier routines in the day 07
+//! firmware, shows up as dead code.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::disasm::decode_fitting;
+use crate::{Instruction, Memory, ParameterMode};
+
+/// Where control can go after a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edge {
+    /// Falling through to the next instruction, including a jump not taken.
+    Next(usize),
+    /// A jump taken to an immediate target.
+    Jump(usize),
+    /// A jump taken to a target computed at run time.
+    Computed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    /// One past the last word of the block's last instruction.
+    pub end: usize,
+    pub instructions: Vec<(usize, Instruction)>,
+    /// Empty for blocks that end in a halt.
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionKind {
+    Code,
+    Dead,
+    Data,
+}
+
+/// A run of words, `start..end`, of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub start: usize,
+    pub end: usize,
+    pub kind: RegionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+    /// Blocks by start address.
+    pub blocks: BTreeMap<usize, Block>,
+    /// Every word of the image, in order, classified.
+    pub regions: Vec<Region>,
+}
+
+impl Cfg {
+    pub fn build(image: &[i64]) -> Self {
+        let memory = Memory::dense(image.to_vec());
+        let len = image.len();
+        let decode = |address| decode_fitting(&memory, len, address);
+
+        // Find every reachable instruction, and the leaders among them.
+        let mut starts = BTreeMap::new();
+        let mut leaders = BTreeSet::new();
+        let mut pending = vec![0];
+        leaders.insert(0);
+        while let Some(address) = pending.pop() {
+            if starts.contains_key(&address) {
+                continue;
+            }
+            let instruction = match decode(address) {
+                Some(instruction) => instruction,
+                None => continue,
+            };
+            let edges = edges(address, &instruction);
+            for edge in &edges {
+                match *edge {
+                    Edge::Jump(target) => {
+                        leaders.insert(target);
+                        pending.push(target);
+                    },
+                    Edge::Next(next) => {
+                        if is_jump(&instruction) {
+                            leaders.insert(next);
+                        }
+                        pending.push(next);
+                    },
+                    Edge::Computed => {},
+                }
+            }
+            starts.insert(address, (instruction, edges));
+        }
+
+        let mut blocks = BTreeMap::new();
+        for &leader in leaders.iter().filter(|leader| starts.contains_key(leader)) {
+            let mut block = Block { start: leader, end: leader, instructions: vec![], edges: vec![] };
+            let mut address = leader;
+            loop {
+                let (instruction, edges) = &starts[&address];
+                block.instructions.push((address, *instruction));
+                block.end = address + instruction.size();
+
+                let next = block.end;
+                if edges.as_slice() != [Edge::Next(next)] || leaders.contains(&next) || !starts.contains_key(&next) {
+                    block.edges = edges.clone();
+                    break;
+                }
+                address = next;
+            }
+            blocks.insert(leader, block);
+        }
+
+        let mut code = vec![false; len];
+        for (&address, (instruction, _)) in &starts {
+            for word in &mut code[address..address + instruction.size()] {
+                *word = true;
+            }
+        }
+
+        Self { blocks, regions: regions(&memory, &code) }
+    }
+
+    /// The block starting at `address`.
+    pub fn block(&self, address: usize) -> Option<&Block> {
+        self.blocks.get(&address)
+    }
+
+    /// Renders the graph in Graphviz DOT, with dead code and data regions
+    /// as unconnected nodes.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph intcode {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for block in self.blocks.values() {
+            let label = block.instructions.iter()
+                .map(|(address, instruction)| format!("{:04}: {}\\l", address, instruction))
+                .collect::<String>();
+            writeln!(dot, "    b{} [label=\"{}\"];", block.start, label).unwrap();
+        }
+
+        let mut missing = BTreeSet::new();
+        let mut computed = false;
+        for block in self.blocks.values() {
+            for edge in &block.edges {
+                match *edge {
+                    Edge::Next(target) | Edge::Jump(target) => {
+                        let style = if let Edge::Jump(_) = edge { " [label=\"jump\"]" } else { "" };
+                        if self.blocks.contains_key(&target) {
+                            writeln!(dot, "    b{} -> b{}{};", block.start, target, style).unwrap();
+                        } else {
+                            missing.insert(target);
+                            writeln!(dot, "    b{} -> x{}{};", block.start, target, style).unwrap();
+                        }
+                    },
+                    Edge::Computed => {
+                        computed = true;
+                        writeln!(dot, "    b{} -> computed [style=dashed];", block.start).unwrap();
+                    },
+                }
+            }
+        }
+
+        for target in missing {
+            writeln!(dot, "    x{} [label=\"{:04}: not code\", shape=plaintext];", target, target).unwrap();
+        }
+        if computed {
+            writeln!(dot, "    computed [label=\"computed jump\", shape=diamond];").unwrap();
+        }
+        for region in &self.regions {
+            let (kind, style) = match region.kind {
+                RegionKind::Code => continue,
+                RegionKind::Dead => ("dead code", "style=dashed"),
+                RegionKind::Data => ("data", "shape=note"),
+            };
+            writeln!(
+                dot,
+                "    r{} [label=\"{:04}-{:04}: {} ({} words)\", {}];",
+                region.start, region.start, region.end - 1, kind, region.end - region.start, style
+            ).unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+fn is_jump(instruction: &Instruction) -> bool {
+    matches!(instruction, Instruction::Jnz(..) | Instruction::Jz(..))
+}
+
+/// The edges out of a single instruction. A jump to a negative immediate
+/// address fails at run time, so it gets no edge.
+fn edges(address: usize, instruction: &Instruction) -> Vec<Edge> {
+    let next = Edge::Next(address + instruction.size());
+
+    use Instruction::*;
+    let (condition, target, jump_if_zero) = match instruction {
+        Halt => return vec![],
+        Jnz(condition, target) => (condition, target, false),
+        Jz(condition, target) => (condition, target, true),
+        _ => return vec![next],
+    };
+
+    let jump = match target.mode {
+        ParameterMode::Immediate if target.value < 0 => None,
+        ParameterMode::Immediate => Some(Edge::Jump(target.value as usize)),
+        _ => Some(Edge::Computed),
+    };
+    match condition.mode {
+        ParameterMode::Immediate if (condition.value == 0) == jump_if_zero => jump.into_iter().collect(),
+        ParameterMode::Immediate => vec![next],
+        _ => jump.into_iter().chain(Some(next)).collect(),
+    }
+}
+
+/// Classifies every word given which ones are reachable code. Unreached
+/// gaps are swept for runs of instructions that would stop or carry on
+/// into reachable code; those are dead code and everything else is data.
+fn regions(memory: &Memory, code: &[bool]) -> Vec<Region> {
+    let mut kinds = code.iter()
+        .map(|&code| if code { RegionKind::Code } else { RegionKind::Data })
+        .collect::<Vec<_>>();
+
+    let mut address = 0;
+    while address < code.len() {
+        if code[address] {
+            address += 1;
+            continue;
+        }
+        let gap_end = code[address..].iter().position(|&code| code).map_or(code.len(), |i| address + i);
+
+        let mut end = address;
+        let mut ends_well = false;
+        while let Some(instruction) = decode_fitting(memory, gap_end, end) {
+            end += instruction.size();
+            ends_well = matches!(instruction, Instruction::Halt) || is_jump(&instruction);
+            if ends_well {
+                break;
+            }
+        }
+        if ends_well || (end == gap_end && end < code.len() && end > address) {
+            for kind in &mut kinds[address..end] {
+                *kind = RegionKind::Dead;
+            }
+            address = end;
+        } else {
+            address += 1;
+        }
+    }
+
+    let mut regions: Vec<Region> = vec![];
+    for (address, kind) in kinds.into_iter().enumerate() {
+        match regions.last_mut() {
+            Some(region) if region.kind == kind => region.end = address + 1,
+            _ => regions.push(Region { start: address, end: address + 1, kind }),
+        }
+    }
+    regions
+}
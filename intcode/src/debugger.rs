@@ -0,0 +1,441 @@
+//! An interactive, gdb-flavoured debugger around `Program`.
+//!
+//! Every instruction executed is remembered along with what it changed, so
+//! the debugger can also run backwards: undo single steps, go back to the
+//! last write to an address, or say which instruction made it. Devices
+//! aren't rewound, only memory, registers and I/O.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::hook::{Event, Hook};
+use crate::{decode_instruction, disasm, Instruction, IntcodeError, Memory, Program, Step};
+
+/// How many executed instructions are remembered for running backwards.
+pub const HISTORY_LIMIT: usize = 1 << 20;
+
+const HELP: &str = "\
+step [n]            (s) execute n instructions, default 1
+continue            (c) run until a breakpoint, watchpoint, input or halt
+step-back [n]       (sb) undo the last n instructions, default 1
+reverse-continue [addr]
+                    (rc) run backwards to a breakpoint or watchpoint, or to
+                         just before the last write to addr
+last-write <addr>   (lw) show which instruction last wrote to addr
+break <addr>        (b) stop before executing the instruction at addr
+delete <addr>           remove a breakpoint
+watch <addr>        (w) stop after an instruction writes to addr
+unwatch <addr>          remove a watchpoint
+info                    list breakpoints and watchpoints
+regs                (r) show i_ptr, relative_base and whether halted
+mem <addr> [len]    (x) dump len words of memory, default 8
+disasm [n]          (d) disassemble around i_ptr, n instructions ahead
+input <v>...        (i) queue input values for IN
+output              (o) show everything output so far
+quit                (q) leave the debugger";
+
+/// Why the debugger handed control back to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stop {
+    Stepped,
+    Breakpoint(usize),
+    Watchpoint { address: usize, old: i64, new: i64 },
+    NeedsInput,
+    Halted,
+    Error(IntcodeError),
+    /// Stepped back as far as the history goes.
+    Start,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Stepped => Ok(()),
+            Stop::Breakpoint(address) => write!(f, "breakpoint at {}", address),
+            Stop::Watchpoint { address, old, new } => write!(f, "watchpoint {}: {} -> {}", address, old, new),
+            Stop::NeedsInput => write!(f, "waiting for input"),
+            Stop::Halted => write!(f, "halted"),
+            Stop::Error(e) => write!(f, "error: {}", e),
+            Stop::Start => write!(f, "start of history"),
+        }
+    }
+}
+
+/// What it takes to undo one executed instruction. Kept small, as there
+/// are up to `HISTORY_LIMIT` of them; the rest can be worked out again.
+#[derive(Debug, Clone, Copy)]
+struct Undo {
+    i_ptr: usize,
+    relative_base: i64,
+    /// The address written to and the word that was there. The word
+    /// written is whatever came after: the next write's old word, or what
+    /// is in memory now.
+    write: Option<(usize, i64)>,
+    /// The words of the instruction, for one that wrote, as it was before
+    /// it ran; the program may since have rewritten it, or itself.
+    words: [i64; 4],
+    /// `memory.len()` before, in case the write grew it.
+    len: usize,
+    /// Whether an input was consumed. It is the word the `IN` wrote, which
+    /// is back in memory by the time the `IN` is undone.
+    input: bool,
+    output: bool,
+    /// Whether the instruction took a step off the budget, as all but a
+    /// `HLT` do.
+    spent: bool,
+}
+
+/// Captures what the instruction just executed did, for the history.
+struct Recorder {
+    len: usize,
+    undo: Option<Undo>,
+}
+
+impl Hook for Recorder {
+    fn on_step(&mut self, event: &Event) {
+        let effects = &event.effects;
+        let mut words = [0; 4];
+        if effects.write.is_some() {
+            let encoded = event.instruction.encode();
+            words[..encoded.len()].copy_from_slice(&encoded);
+        }
+        self.undo = Some(Undo {
+            i_ptr: event.i_ptr,
+            relative_base: event.relative_base,
+            write: effects.write.map(|write| (write.address, write.old)),
+            words,
+            len: self.len,
+            input: effects.input.is_some(),
+            output: effects.output.is_some(),
+            spent: !matches!(event.instruction, Instruction::Halt),
+        });
+    }
+}
+
+/// The last remembered write to an address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LastWrite {
+    /// Instructions executed before the one that wrote.
+    pub step: u64,
+    pub i_ptr: usize,
+    pub old: i64,
+    pub new: i64,
+    /// The instruction that wrote, as it was then. `None` for one from a
+    /// custom `Opcodes` registry.
+    pub instruction: Option<Instruction>,
+}
+
+pub struct Debugger {
+    pub program: Program,
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+    /// Instructions executed so far, less those stepped back over.
+    steps: u64,
+    history: VecDeque<Undo>,
+}
+
+impl Debugger {
+    pub fn new(program: Program) -> Self {
+        Self {
+            program,
+            input: VecDeque::new(),
+            output: vec![],
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            steps: 0,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn add_watchpoint(&mut self, address: usize) {
+        self.watchpoints.insert(address);
+    }
+
+    /// Executes one instruction, reporting a write to a watched address.
+    pub fn step(&mut self) -> Stop {
+        // A halted program reports its `HLT` again without doing anything,
+        // which isn't worth remembering.
+        let halted = self.program.halted;
+        let mut recorder = Recorder { len: self.program.memory.len(), undo: None };
+        let stop = match self.program.step_with(&mut self.input, &mut recorder) {
+            Ok(Step::Ran) => Stop::Stepped,
+            Ok(Step::Output(val)) => {
+                self.output.push(val);
+                Stop::Stepped
+            },
+            Ok(Step::NeedsInput) => Stop::NeedsInput,
+            Ok(Step::Halted) => Stop::Halted,
+            Err(e) => Stop::Error(e),
+        };
+
+        let undo = match recorder.undo {
+            Some(undo) if !halted => undo,
+            _ => return stop,
+        };
+        self.steps += 1;
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(undo);
+
+        match undo.write {
+            Some((address, old)) if stop == Stop::Stepped && self.watchpoints.contains(&address) => {
+                Stop::Watchpoint { address, old, new: self.program.memory[address] }
+            },
+            _ => stop,
+        }
+    }
+
+    /// Undoes the last instruction executed, reporting a write to a watched
+    /// address, or `Start` if there is nothing left to undo.
+    pub fn step_back(&mut self) -> Stop {
+        let undo = match self.history.pop_back() {
+            Some(undo) => undo,
+            None => return Stop::Start,
+        };
+        self.steps -= 1;
+        self.program.i_ptr = undo.i_ptr;
+        self.program.relative_base = undo.relative_base;
+        self.program.halted = false;
+        if undo.spent {
+            if let Some(steps) = &mut self.program.budget.steps {
+                *steps += 1;
+            }
+        }
+        if undo.output {
+            self.output.pop();
+        }
+
+        let mut stop = Stop::Stepped;
+        if let Some((address, old)) = undo.write {
+            let new = self.program.memory[address];
+            if undo.input {
+                self.input.push_front(new);
+            }
+            // Straight to memory: the old word is what was in memory, even
+            // under a device.
+            self.program.memory.set(address, old);
+            if self.watchpoints.contains(&address) {
+                stop = Stop::Watchpoint { address, old, new };
+            }
+        }
+        self.program.memory.truncate(undo.len);
+        stop
+    }
+
+    /// Runs backwards to a breakpoint or a write to a watched address, or
+    /// with `address`, to just before the last write to it.
+    pub fn reverse(&mut self, address: Option<usize>) -> Stop {
+        loop {
+            let write = self.history.back()
+                .and_then(|undo| undo.write)
+                .map(|(written, _)| (written, self.program.memory[written]));
+            let stop = self.step_back();
+            match (address, write) {
+                (Some(address), Some((written, new))) if written == address => {
+                    break Stop::Watchpoint { address, old: self.program.memory[address], new };
+                },
+                (Some(_), _) if stop != Stop::Start => {},
+                _ if stop != Stop::Stepped => break stop,
+                _ if self.breakpoints.contains(&self.program.i_ptr) => break Stop::Breakpoint(self.program.i_ptr),
+                _ => {},
+            }
+        }
+    }
+
+    /// The last remembered write to `address`.
+    pub fn last_write(&self, address: usize) -> Option<LastWrite> {
+        let first = self.steps - self.history.len() as u64;
+        let (i, undo) = self.history.iter().enumerate().rev()
+            .find(|(_, undo)| undo.write.is_some_and(|(written, _)| written == address))?;
+        let (_, old) = undo.write?;
+        // Being the last write, what it wrote is still there.
+        let instruction = decode_instruction(&Memory::dense(undo.words.to_vec()), 0).ok();
+        Some(LastWrite { step: first + i as u64, i_ptr: undo.i_ptr, old, new: self.program.memory[address], instruction })
+    }
+
+    /// Runs until something worth stopping for happens. A breakpoint at the
+    /// current instruction doesn't stop it from being executed.
+    pub fn resume(&mut self) -> Stop {
+        loop {
+            match self.step() {
+                Stop::Stepped => {},
+                stop => break stop,
+            }
+            if self.breakpoints.contains(&self.program.i_ptr) {
+                break Stop::Breakpoint(self.program.i_ptr);
+            }
+        }
+    }
+
+    /// Runs a single command line and returns what to print.
+    pub fn command(&mut self, line: &str) -> String {
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => return String::new(),
+        };
+        let args = words.map(str::parse::<i64>).collect::<Result<Vec<i64>, _>>();
+        let args = match args {
+            Ok(args) => args,
+            Err(e) => return format!("bad argument: {}", e),
+        };
+        let address = |i: usize| args.get(i).filter(|&&a| a >= 0).map(|&a| a as usize);
+        // A count defaults to 1 when left out, and is bad when negative.
+        let count = |i: usize| if args.len() > i { address(i) } else { Some(1) };
+
+        match command {
+            "step" | "s" => match count(0) {
+                Some(n) => {
+                    let mut stop = Stop::Stepped;
+                    for _ in 0..n {
+                        stop = self.step();
+                        if stop != Stop::Stepped {
+                            break;
+                        }
+                    }
+                    self.describe(&stop)
+                },
+                None => "usage: step [n]".to_string(),
+            },
+            "continue" | "c" => {
+                let stop = self.resume();
+                self.describe(&stop)
+            },
+            "step-back" | "sb" => match count(0) {
+                Some(n) => {
+                    let mut stop = Stop::Stepped;
+                    for _ in 0..n {
+                        stop = self.step_back();
+                        if stop != Stop::Stepped {
+                            break;
+                        }
+                    }
+                    self.describe(&stop)
+                },
+                None => "usage: step-back [n]".to_string(),
+            },
+            "reverse-continue" | "rc" => {
+                let stop = self.reverse(address(0));
+                self.describe(&stop)
+            },
+            "last-write" | "lw" => match address(0) {
+                Some(a) => match self.last_write(a) {
+                    Some(write) => match write.instruction {
+                        Some(instruction) => format!(
+                            "{}: {} -> {}\nby step {}, {:04}: {}",
+                            a, write.old, write.new, write.step, write.i_ptr, instruction,
+                        ),
+                        None => format!("{}: {} -> {}\nby step {}, {:04}", a, write.old, write.new, write.step, write.i_ptr),
+                    },
+                    None => format!("{} not written since step {}", a, self.steps - self.history.len() as u64),
+                },
+                None => "usage: last-write <addr>".to_string(),
+            },
+            "break" | "b" => match address(0) {
+                Some(a) => {
+                    self.add_breakpoint(a);
+                    format!("breakpoint at {}", a)
+                },
+                None => "usage: break <addr>".to_string(),
+            },
+            "delete" => match address(0) {
+                Some(a) if self.breakpoints.remove(&a) => format!("deleted breakpoint at {}", a),
+                _ => "no such breakpoint".to_string(),
+            },
+            "watch" | "w" => match address(0) {
+                Some(a) => {
+                    self.add_watchpoint(a);
+                    format!("watchpoint at {}", a)
+                },
+                None => "usage: watch <addr>".to_string(),
+            },
+            "unwatch" => match address(0) {
+                Some(a) if self.watchpoints.remove(&a) => format!("deleted watchpoint at {}", a),
+                _ => "no such watchpoint".to_string(),
+            },
+            "info" => format!("breakpoints: {:?}\nwatchpoints: {:?}", self.breakpoints, self.watchpoints),
+            "regs" | "r" => format!(
+                "i_ptr: {}\nrelative_base: {}\nhalted: {}\nsteps: {}\nqueued input: {:?}",
+                self.program.i_ptr, self.program.relative_base, self.program.halted, self.steps, self.input,
+            ),
+            "mem" | "x" => match address(0) {
+                Some(start) => {
+                    // Past the end of memory is all zeros; there's no need
+                    // to show more of them than there is memory.
+                    let len = address(1).unwrap_or(8).min(self.program.memory.len().max(8));
+                    self.program.memory.range(start, start.saturating_add(len))
+                        .iter()
+                        .enumerate()
+                        .map(|(i, val)| format!("{:04}: {}", start + i, val))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                },
+                None => "usage: mem <addr> [len]".to_string(),
+            },
+            "disasm" | "d" => self.disassembly(address(0).unwrap_or(8)),
+            "input" | "i" => {
+                self.input.extend(&args);
+                format!("queued input: {:?}", self.input)
+            },
+            "output" | "o" => format!("{:?}", self.output),
+            "help" | "h" => HELP.to_string(),
+            _ => format!("unknown command {:?}, try help", command),
+        }
+    }
+
+    /// Reads commands from `input` until `quit` or end of input.
+    pub fn repl(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "(intcode) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            if let "quit" | "q" = line.trim() {
+                break;
+            }
+            let text = self.command(&line);
+            if !text.is_empty() {
+                writeln!(output, "{}", text)?;
+            }
+            write!(output, "(intcode) ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Describes a stop, followed by the instruction about to run.
+    fn describe(&self, stop: &Stop) -> String {
+        let mut text = stop.to_string();
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text + &self.disassembly(1)
+    }
+
+    /// A few lines before `i_ptr`, marked with `=>`, and `n` from it on.
+    fn disassembly(&self, n: usize) -> String {
+        let i_ptr = self.program.i_ptr;
+        let memory = &self.program.memory;
+        let before = disasm::disassemble_range(memory, i_ptr.saturating_sub(8), i_ptr);
+        // Every instruction takes at least one word, so there are never more
+        // to show than there are words in memory.
+        let n = n.min(memory.len().max(1));
+        let after = disasm::disassemble_range(memory, i_ptr, i_ptr.saturating_add(n.saturating_mul(4)));
+
+        before.iter()
+            .skip(before.len().saturating_sub(3))
+            .map(|line| format!("   {}", line))
+            .chain(after.iter().take(n).enumerate().map(|(i, line)| {
+                format!("{}{}", if i == 0 { "=> " } else { "   " }, line)
+            }))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
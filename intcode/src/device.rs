@@ -0,0 +1,116 @@
+//! Memory-mapped devices.
+//!
+//! `Program::attach` maps a `Device` over a range of addresses. Every read
+//! and write an instruction makes there goes to the device instead of
+//! memory; instructions themselves are still fetched from memory, and
+//! `Program::memory` is left as it was under the device.
+//!
+//! ```
+//! # use intcode::device::Framebuffer;
+//! # use intcode::Program;
+//! // Draws a diagonal line on a 3x3 screen at 100.
+//! let mut program = Program::init(vec![1101, 1, 0, 100, 1101, 1, 0, 104, 1101, 1, 0, 108, 99]);
+//! let screen = program.attach(100, 9, Framebuffer::new(3, 3));
+//! program.execute(&[]).unwrap();
+//! assert_eq!(screen.lock().unwrap().render(), "#..\n.#.\n..#\n");
+//! ```
+
+use std::time::Instant;
+
+use crate::Word;
+
+/// Virtual hardware behind a range of addresses. Offsets count from the
+/// start of the range.
+pub trait Device<W = i64>: Send {
+    fn read(&mut self, offset: usize) -> W;
+
+    fn write(&mut self, offset: usize, value: W);
+}
+
+/// Reads as the milliseconds since it was made. Writes are ignored.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    start: Instant,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Word> Device<W> for Clock {
+    fn read(&mut self, _: usize) -> W {
+        W::from_i64(self.start.elapsed().as_millis() as i64)
+    }
+
+    fn write(&mut self, _: usize, _: W) {}
+}
+
+/// A xorshift generator reading as non-negative 31-bit numbers, the same
+/// sequence for the same seed. Writing a word reseeds it.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // Small seeds would start on small numbers, and xorshift gets
+        // stuck on zero.
+        Self { state: (seed ^ 0x9e37_79b9_7f4a_7c15).max(1) }
+    }
+}
+
+impl<W: Word> Device<W> for Random {
+    fn read(&mut self, _: usize) -> W {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        W::from_i64((self.state >> 33) as i64)
+    }
+
+    fn write(&mut self, _: usize, value: W) {
+        *self = Random::new(value.to_i64().unwrap_or(0) as u64);
+    }
+}
+
+/// A screen of `width * height` pixels, one word each, row by row.
+#[derive(Debug, Clone)]
+pub struct Framebuffer<W = i64> {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<W>,
+}
+
+impl<W: Word> Framebuffer<W> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![W::default(); width * height] }
+    }
+
+    /// The screen as text, `#` for non-zero pixels and `.` for zero.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for row in self.pixels.chunks(self.width.max(1)) {
+            out.extend(row.iter().map(|pixel| if pixel.is_zero() { '.' } else { '#' }));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<W: Word + Send> Device<W> for Framebuffer<W> {
+    fn read(&mut self, offset: usize) -> W {
+        self.pixels[offset].clone()
+    }
+
+    fn write(&mut self, offset: usize, value: W) {
+        self.pixels[offset] = value;
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::{decode_instruction, Instruction, Memory, ParameterMode};
+
+/// One line of a disassembly listing: either a decoded instruction or a
+/// single word of data.
+#[derive(Debug)]
+pub struct Line {
+    pub address: usize,
+    pub words: Vec<i64>,
+    pub instruction: Option<Instruction>,
+}
+
+impl fmt::Display for Line {
+    /// Renders e.g. `0012: ADD [rb+3], #5, @20          ; 21101,3,5,20`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match &self.instruction {
+            Some(instruction) => instruction.to_string(),
+            None => format!("DATA {}", self.words[0]),
+        };
+        let words = self.words.iter().map(i64::to_string).collect::<Vec<_>>().join(",");
+        write!(f, "{:04}: {:<32} ; {}", self.address, text, words)
+    }
+}
+
+/// Disassembles `image` with a linear sweep from address 0. Words that don't
+/// decode to an instruction that fits in the image become `DATA`.
+pub fn disassemble(image: &[i64]) -> Vec<Line> {
+    disassemble_range(&Memory::dense(image.to_vec()), 0, image.len())
+}
+
+/// Disassembles `start..end` of a running program's memory with a linear
+/// sweep from `start`. Only instructions that end by `end` are decoded.
+pub fn disassemble_range(memory: &Memory, start: usize, end: usize) -> Vec<Line> {
+    sweep(memory, start, end, |address| decode_listed(memory, end, address))
+}
+
+/// Disassembles only the code reachable from address 0 by falling through
+/// and by following `JNZ`/`JZ` with immediate targets. Everything else,
+/// including code only reachable through computed jumps, becomes `DATA`.
+pub fn disassemble_reachable(image: &[i64]) -> Vec<Line> {
+    let memory = Memory::dense(image.to_vec());
+    let starts = reachable(&memory, image.len());
+    sweep(&memory, 0, image.len(), |address| {
+        if starts.contains(&address) {
+            decode_listed(&memory, image.len(), address)
+        } else {
+            None
+        }
+    })
+}
+
+/// Finds the address of every instruction reachable from address 0.
+pub fn reachable(memory: &Memory, len: usize) -> BTreeSet<usize> {
+    let mut starts = BTreeSet::new();
+    let mut pending = vec![0];
+
+    while let Some(address) = pending.pop() {
+        if !starts.insert(address) {
+            continue;
+        }
+        let instruction = match decode_fitting(memory, len, address) {
+            Some(instruction) => instruction,
+            None => {
+                starts.remove(&address);
+                continue;
+            },
+        };
+        use Instruction::*;
+        match &instruction {
+            Halt => {},
+            Jnz(_, target) | Jz(_, target) => {
+                if target.mode == ParameterMode::Immediate && target.value >= 0 {
+                    pending.push(target.value as usize);
+                }
+                pending.push(address + instruction.size());
+            },
+            _ => pending.push(address + instruction.size()),
+        }
+    }
+
+    starts
+}
+
+pub(crate) fn decode_fitting(memory: &Memory, len: usize, address: usize) -> Option<Instruction> {
+    decode_instruction(memory, address)
+        .ok()
+        .filter(|instruction| address + instruction.size() <= len)
+}
+
+/// `decode_fitting`, limited to instructions the assembler writes back
+/// word for word: no stray mode digits in the opcode (`99999` runs as a
+/// halt) and no immediate operand written to. Anything else is listed as
+/// data so that listings reassemble into the same image.
+fn decode_listed(memory: &Memory, len: usize, address: usize) -> Option<Instruction> {
+    decode_fitting(memory, len, address).filter(|instruction| {
+        // Two digits of opcode, then one mode digit per parameter.
+        memory[address] < 10_i64.pow(instruction.size() as u32 + 1)
+            && instruction.write_parameter().is_none_or(|parameter| parameter.mode != ParameterMode::Immediate)
+    })
+}
+
+pub(crate) fn sweep(
+    memory: &Memory,
+    start: usize,
+    end: usize,
+    mut decode: impl FnMut(usize) -> Option<Instruction>
+) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut address = start;
+
+    while address < end {
+        let line = match decode(address) {
+            Some(instruction) => Line {
+                address,
+                words: memory.range(address, address + instruction.size()),
+                instruction: Some(instruction),
+            },
+            None => Line { address, words: vec![memory[address]], instruction: None },
+        };
+        address += line.words.len();
+        lines.push(line);
+    }
+
+    lines
+}
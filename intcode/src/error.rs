@@ -0,0 +1,75 @@
+use std::error::Error;
+use std::fmt;
+
+/// Everything that can go wrong while decoding or executing an Intcode
+/// program. Every variant carries `addr`, the instruction pointer of the
+/// instruction that failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntcodeError<W = i64> {
+    UnknownOpcode { addr: usize, value: W },
+    BadParameterMode { addr: usize, param_index: usize, mode: u8 },
+    WriteToImmediate { addr: usize },
+    NegativeAddress { addr: usize, address: W },
+    OutOfBounds { addr: usize, address: usize },
+    /// An `ADD`, `MUL` or relative base calculation overflowed under
+    /// `Overflow::Trap` or `Overflow::Promote`.
+    Overflow { addr: usize },
+    /// The step budget or the deadline ran out before the instruction at
+    /// `addr` ran. Nothing was lost: raise the limit and run again.
+    BudgetExceeded { addr: usize },
+    /// The loop detector saw the machine come back to a state it was in
+    /// before, without any I/O in between, so it would never stop.
+    InfiniteLoop { addr: usize },
+}
+
+impl<W> IntcodeError<W> {
+    /// The instruction pointer at which the error occurred.
+    pub fn addr(&self) -> usize {
+        use IntcodeError::*;
+        match self {
+            UnknownOpcode { addr, .. }
+            | BadParameterMode { addr, .. }
+            | WriteToImmediate { addr }
+            | NegativeAddress { addr, .. }
+            | OutOfBounds { addr, .. }
+            | Overflow { addr }
+            | BudgetExceeded { addr }
+            | InfiniteLoop { addr } => *addr,
+        }
+    }
+
+    /// The same error, with the word it carries (if any) converted by `f`.
+    pub fn map<V>(self, f: impl FnOnce(W) -> V) -> IntcodeError<V> {
+        use IntcodeError::*;
+        match self {
+            UnknownOpcode { addr, value } => UnknownOpcode { addr, value: f(value) },
+            BadParameterMode { addr, param_index, mode } => BadParameterMode { addr, param_index, mode },
+            WriteToImmediate { addr } => WriteToImmediate { addr },
+            NegativeAddress { addr, address } => NegativeAddress { addr, address: f(address) },
+            OutOfBounds { addr, address } => OutOfBounds { addr, address },
+            Overflow { addr } => Overflow { addr },
+            BudgetExceeded { addr } => BudgetExceeded { addr },
+            InfiniteLoop { addr } => InfiniteLoop { addr },
+        }
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for IntcodeError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use IntcodeError::*;
+        match self {
+            UnknownOpcode { addr, value } => write!(f, "unknown opcode {} at {}", value, addr),
+            BadParameterMode { addr, param_index, mode } => {
+                write!(f, "unknown mode {} for parameter {} at {}", mode, param_index, addr)
+            },
+            WriteToImmediate { addr } => write!(f, "write to an immediate parameter at {}", addr),
+            NegativeAddress { addr, address } => write!(f, "negative address {} at {}", address, addr),
+            OutOfBounds { addr, address } => write!(f, "address {} out of bounds at {}", address, addr),
+            Overflow { addr } => write!(f, "arithmetic overflow at {}", addr),
+            BudgetExceeded { addr } => write!(f, "step budget or deadline exceeded at {}", addr),
+            InfiniteLoop { addr } => write!(f, "infinite loop without I/O at {}", addr),
+        }
+    }
+}
+
+impl<W: fmt::Debug + fmt::Display> Error for IntcodeError<W> {}
@@ -0,0 +1,199 @@
+//! Running machines as futures on a single-threaded executor.
+//!
+//! Each machine is a task; `In` awaits its channel and `Out` sends to one,
+//! so a whole network runs on one thread with no scheduler of its own.
+//! Everything here is built on `std` alone.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate::{Input, IntcodeError, Output, Program, StepResult};
+
+struct Shared {
+    queue: VecDeque<i64>,
+    senders: usize,
+    waker: Option<Waker>,
+}
+
+/// Creates an unbounded single-threaded channel.
+pub fn channel() -> (AsyncSender, AsyncReceiver) {
+    let shared = Rc::new(RefCell::new(Shared { queue: VecDeque::new(), senders: 1, waker: None }));
+    (AsyncSender { shared: shared.clone() }, AsyncReceiver { shared })
+}
+
+pub struct AsyncSender {
+    shared: Rc<RefCell<Shared>>,
+}
+
+pub struct AsyncReceiver {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl AsyncSender {
+    pub fn send(&self, value: i64) {
+        let mut shared = self.shared.borrow_mut();
+        shared.queue.push_back(value);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Clone for AsyncSender {
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().senders += 1;
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for AsyncSender {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.senders -= 1;
+        if shared.senders == 0 {
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Output for AsyncSender {
+    fn output(&mut self, value: i64) {
+        self.send(value);
+    }
+}
+
+impl AsyncReceiver {
+    pub fn try_recv(&mut self) -> Option<i64> {
+        self.shared.borrow_mut().queue.pop_front()
+    }
+
+    /// Waits until a value is available (`true`) or every sender is gone
+    /// and the channel is empty (`false`). Doesn't take the value.
+    pub fn ready(&self) -> impl Future<Output = bool> + '_ {
+        Ready { receiver: self }
+    }
+}
+
+impl Input for AsyncReceiver {
+    fn next_input(&mut self) -> Option<i64> {
+        self.try_recv()
+    }
+}
+
+struct Ready<'a> {
+    receiver: &'a AsyncReceiver,
+}
+
+impl<'a> Future for Ready<'a> {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<bool> {
+        let mut shared = self.receiver.shared.borrow_mut();
+        if !shared.queue.is_empty() {
+            Poll::Ready(true)
+        } else if shared.senders == 0 {
+            Poll::Ready(false)
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Runs `program` until it halts, or until it needs input and every sender
+/// of `input` has been dropped. The program is handed back in whichever
+/// state it stopped.
+pub async fn run(
+    mut program: Program,
+    mut input: AsyncReceiver,
+    mut output: AsyncSender
+) -> Result<Program, IntcodeError> {
+    loop {
+        match program.run_io(&mut input, &mut output)? {
+            StepResult::NeedsInput => {
+                if !input.ready().await {
+                    return Ok(program);
+                }
+            },
+            _ => return Ok(program),
+        }
+    }
+}
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+struct TaskWaker {
+    id: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.ready.lock().unwrap().push_back(self.id);
+    }
+}
+
+/// The result of a spawned task, available once the executor has run it to
+/// completion.
+pub struct TaskHandle<T> {
+    result: Rc<RefCell<Option<T>>>,
+}
+
+impl<T> TaskHandle<T> {
+    pub fn take(&self) -> Option<T> {
+        self.result.borrow_mut().take()
+    }
+}
+
+/// A single-threaded executor that polls tasks in the order they were woken.
+#[derive(Default)]
+pub struct LocalExecutor {
+    tasks: Vec<Option<Task>>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl LocalExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn<T: 'static>(&mut self, future: impl Future<Output = T> + 'static) -> TaskHandle<T> {
+        let result = Rc::new(RefCell::new(None));
+        let slot = result.clone();
+        self.tasks.push(Some(Box::pin(async move {
+            *slot.borrow_mut() = Some(future.await);
+        })));
+        self.ready.lock().unwrap().push_back(self.tasks.len() - 1);
+        TaskHandle { result }
+    }
+
+    /// Polls tasks until none can make progress, returning how many are
+    /// left unfinished. Anything other than zero means the remaining tasks
+    /// are all waiting on channels that will never be written to.
+    pub fn run(&mut self) -> usize {
+        loop {
+            let id = match self.ready.lock().unwrap().pop_front() {
+                Some(id) => id,
+                None => break,
+            };
+            let task = match &mut self.tasks[id] {
+                Some(task) => task,
+                None => continue,
+            };
+
+            let waker = Waker::from(Arc::new(TaskWaker { id, ready: self.ready.clone() }));
+            if task.as_mut().poll(&mut Context::from_waker(&waker)).is_ready() {
+                self.tasks[id] = None;
+            }
+        }
+
+        self.tasks.iter().filter(|task| task.is_some()).count()
+    }
+}
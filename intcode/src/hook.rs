@@ -0,0 +1,68 @@
+use crate::Instruction;
+
+/// Observes every instruction a `Program` executes.
+pub trait Hook<W = i64> {
+    fn on_step(&mut self, event: &Event<W>);
+}
+
+/// One executed instruction: the state before it ran and what it did.
+#[derive(Debug)]
+pub struct Event<'a, W = i64> {
+    pub i_ptr: usize,
+    pub relative_base: W,
+    pub instruction: &'a Instruction<W>,
+    /// Resolved operands in parameter order: the value read for inputs and
+    /// the address for the parameter written to. Unused slots are zero.
+    pub operands: [W; 3],
+    pub effects: Effects<W>,
+}
+
+impl<'a, W> Event<'a, W> {
+    /// The resolved operands actually used by the instruction.
+    pub fn operands(&self) -> &[W] {
+        &self.operands[..self.instruction.size() - 1]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Effects<W = i64> {
+    pub write: Option<MemoryWrite<W>>,
+    /// The new `i_ptr`, if a jump was taken.
+    pub jump: Option<usize>,
+    pub input: Option<W>,
+    pub output: Option<W>,
+    /// The new relative base, after an `ARB`.
+    pub relative_base: Option<W>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryWrite<W = i64> {
+    pub address: usize,
+    pub old: W,
+    pub new: W,
+}
+
+impl<W> Hook<W> for () {
+    fn on_step(&mut self, _: &Event<W>) {}
+}
+
+impl<W, H: Hook<W> + ?Sized> Hook<W> for &mut H {
+    fn on_step(&mut self, event: &Event<W>) {
+        (**self).on_step(event);
+    }
+}
+
+impl<W, H: Hook<W>> Hook<W> for Option<H> {
+    fn on_step(&mut self, event: &Event<W>) {
+        if let Some(hook) = self {
+            hook.on_step(event);
+        }
+    }
+}
+
+impl<W, A: Hook<W>, B: Hook<W>> Hook<W> for (A, B) {
+    fn on_step(&mut self, event: &Event<W>) {
+        self.0.on_step(event);
+        self.1.on_step(event);
+    }
+}
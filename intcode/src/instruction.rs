@@ -0,0 +1,216 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{IntcodeError, Memory, Program, Word};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction<W = i64> {
+    Add(Parameter<W>, Parameter<W>, Parameter<W>),
+    Mul(Parameter<W>, Parameter<W>, Parameter<W>),
+    In(Parameter<W>),
+    Out(Parameter<W>),
+    Halt,
+    Jnz(Parameter<W>, Parameter<W>),
+    Jz(Parameter<W>, Parameter<W>),
+    Lt(Parameter<W>, Parameter<W>, Parameter<W>),
+    Eq(Parameter<W>, Parameter<W>, Parameter<W>),
+    ChangeBase(Parameter<W>),
+    /// An instruction from a custom `Opcodes` registry. Only the first
+    /// `arity` parameters are used; `writes` has bit `i` set if parameter
+    /// `i` is written to.
+    Custom { opcode: i64, mnemonic: &'static str, arity: u8, writes: u8, parameters: [Parameter<W>; 3] },
+}
+
+impl<W> Instruction<W> {
+    pub fn mnemonic(&self) -> &'static str {
+        use Instruction::*;
+        match self {
+            Add(..) => "ADD",
+            Mul(..) => "MUL",
+            In(..) => "IN",
+            Out(..) => "OUT",
+            Halt => "HLT",
+            Jnz(..) => "JNZ",
+            Jz(..) => "JZ",
+            Lt(..) => "LT",
+            Eq(..) => "EQ",
+            ChangeBase(..) => "ARB",
+            Custom { mnemonic, .. } => mnemonic,
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<&Parameter<W>> {
+        use Instruction::*;
+        match self {
+            Halt => vec![],
+            In(p1) | Out(p1) | ChangeBase(p1) => vec![p1],
+            Jnz(p1, p2) | Jz(p1, p2) => vec![p1, p2],
+            Add(p1, p2, p3) | Mul(p1, p2, p3) | Lt(p1, p2, p3) | Eq(p1, p2, p3) => vec![p1, p2, p3],
+            Custom { arity, parameters, .. } => parameters[..*arity as usize].iter().collect(),
+        }
+    }
+
+    /// The parameter the instruction writes its result to, if any.
+    pub fn write_parameter(&self) -> Option<&Parameter<W>> {
+        use Instruction::*;
+        match self {
+            Add(_, _, p3) | Mul(_, _, p3) | Lt(_, _, p3) | Eq(_, _, p3) => Some(p3),
+            In(p1) => Some(p1),
+            Custom { arity, writes, parameters, .. } => {
+                (0..*arity as usize).find(|i| writes & 1 << i != 0).map(|i| &parameters[i])
+            },
+            _ => None,
+        }
+    }
+
+    /// The number of words the instruction occupies, opcode included.
+    pub fn size(&self) -> usize {
+        1 + self.parameters().len()
+    }
+
+    /// The opcode without parameter modes.
+    pub fn opcode(&self) -> i64 {
+        use Instruction::*;
+        match self {
+            Add(..) => 1,
+            Mul(..) => 2,
+            In(..) => 3,
+            Out(..) => 4,
+            Jnz(..) => 5,
+            Jz(..) => 6,
+            Lt(..) => 7,
+            Eq(..) => 8,
+            ChangeBase(..) => 9,
+            Halt => 99,
+            Custom { opcode, .. } => *opcode,
+        }
+    }
+}
+
+impl<W: Word> Instruction<W> {
+    /// The words `decode_instruction` decodes this instruction from.
+    pub fn encode(&self) -> Vec<W> {
+        let parameters = self.parameters();
+        let modes = parameters.iter().enumerate().map(|(i, parameter)| {
+            MODE_SCALE[i] * match parameter.mode {
+                ParameterMode::Position => 0,
+                ParameterMode::Immediate => 1,
+                ParameterMode::Relative => 2,
+            }
+        });
+        let mut words = vec![W::from_i64(self.opcode() + modes.sum::<i64>())];
+        words.extend(parameters.into_iter().map(|parameter| parameter.value.clone()));
+        words
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Instruction<W> {
+    /// Renders the instruction as assembly, e.g. `ADD [rb+3], #5, @20`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        for (i, param) in self.parameters().into_iter().enumerate() {
+            write!(f, "{}{}", if i == 0 { " " } else { ", " }, param)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterMode {
+    Immediate,
+    Position,
+    Relative,
+}
+
+impl TryFrom<u8> for ParameterMode {
+    type Error = u8;
+
+    fn try_from(byte: u8) -> Result<Self, u8> {
+        use ParameterMode::*;
+        match byte {
+            0 => Ok(Position),
+            1 => Ok(Immediate),
+            2 => Ok(Relative),
+            _ => Err(byte),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parameter<W = i64> {
+    pub mode: ParameterMode,
+    pub value: W,
+}
+
+impl<W: Word> Parameter<W> {
+    /// Reads the value this parameter refers to.
+    pub fn get_value(&self, program: &Program<W>) -> Result<W, IntcodeError<W>> {
+        match self.mode {
+            ParameterMode::Immediate => Ok(self.value.clone()),
+            _ => program.read(self.get_value_write(program)?),
+        }
+    }
+
+    /// Resolves the address this parameter writes to.
+    pub fn get_value_write(&self, program: &Program<W>) -> Result<usize, IntcodeError<W>> {
+        match self.mode {
+            ParameterMode::Immediate => Err(IntcodeError::WriteToImmediate { addr: program.i_ptr }),
+            ParameterMode::Position => program.address(self.value.clone()),
+            ParameterMode::Relative => program.address(program.add(self.value.clone(), program.relative_base.clone())?),
+        }
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Parameter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            ParameterMode::Immediate => write!(f, "#{}", self.value),
+            ParameterMode::Position => write!(f, "@{}", self.value),
+            ParameterMode::Relative => write!(f, "[rb{:+}]", self.value),
+        }
+    }
+}
+
+/// Decodes the instruction starting at `i_ptr`.
+pub fn decode_instruction<W: Word>(memory: &Memory<W>, i_ptr: usize) -> Result<Instruction<W>, IntcodeError<W>> {
+    let unknown = || IntcodeError::UnknownOpcode { addr: i_ptr, value: memory[i_ptr].clone() };
+    let opcode = match memory[i_ptr].to_i64() {
+        Some(opcode) if opcode >= 0 => opcode,
+        _ => return Err(unknown()),
+    };
+
+    let param = |i| decode_parameter(memory, i_ptr, opcode, i);
+
+    use Instruction::*;
+    match opcode % 100 {
+        1 => Ok(Add(param(0)?, param(1)?, param(2)?)),
+        2 => Ok(Mul(param(0)?, param(1)?, param(2)?)),
+        3 => Ok(In(param(0)?)),
+        4 => Ok(Out(param(0)?)),
+        5 => Ok(Jnz(param(0)?, param(1)?)),
+        6 => Ok(Jz(param(0)?, param(1)?)),
+        7 => Ok(Lt(param(0)?, param(1)?, param(2)?)),
+        8 => Ok(Eq(param(0)?, param(1)?, param(2)?)),
+        9 => Ok(ChangeBase(param(0)?)),
+        99 => Ok(Halt),
+        _ => Err(unknown()),
+    }
+}
+
+/// Decodes parameter `i` of the instruction at `i_ptr`, whose opcode word
+/// is `opcode`.
+pub(crate) fn decode_parameter<W: Word>(
+    memory: &Memory<W>,
+    i_ptr: usize,
+    opcode: i64,
+    i: usize,
+) -> Result<Parameter<W>, IntcodeError<W>> {
+    // The mode of parameter `i` is digit `i + 2` of the opcode, counting
+    // from the right.
+    let digit = (opcode / MODE_SCALE[i] % 10) as u8;
+    let mode = ParameterMode::try_from(digit)
+        .map_err(|mode| IntcodeError::BadParameterMode { addr: i_ptr, param_index: i, mode })?;
+    Ok(Parameter { mode, value: memory[1 + i_ptr + i].clone() })
+}
+
+const MODE_SCALE: [i64; 3] = [100, 1000, 10000];
@@ -0,0 +1,100 @@
+//! Pluggable input sources and output sinks for `Program::run`.
+
+use std::collections::VecDeque;
+
+/// Where `In` gets its values from. Values are pulled one at a time, only
+/// when an `In` executes.
+pub trait Input<W = i64> {
+    /// The next value, or `None` if there is nothing to give right now.
+    fn next_input(&mut self) -> Option<W>;
+}
+
+/// Where `Out` sends its values.
+pub trait Output<W = i64> {
+    fn output(&mut self, value: W);
+}
+
+/// What a program stopped running for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepResult<W = i64> {
+    NeedsInput,
+    Output(W),
+    Halted,
+}
+
+impl<W> Input<W> for VecDeque<W> {
+    fn next_input(&mut self) -> Option<W> {
+        self.pop_front()
+    }
+}
+
+impl<W, I: Input<W> + ?Sized> Input<W> for &mut I {
+    fn next_input(&mut self) -> Option<W> {
+        (**self).next_input()
+    }
+}
+
+impl<W> Output<W> for Vec<W> {
+    fn output(&mut self, value: W) {
+        self.push(value);
+    }
+}
+
+impl<W> Output<W> for VecDeque<W> {
+    fn output(&mut self, value: W) {
+        self.push_back(value);
+    }
+}
+
+impl<W, O: Output<W> + ?Sized> Output<W> for &mut O {
+    fn output(&mut self, value: W) {
+        (**self).output(value);
+    }
+}
+
+/// Reads through a slice, keeping track of how much of it was used.
+#[derive(Debug, Clone)]
+pub struct SliceInput<'a, W = i64> {
+    values: &'a [W],
+    consumed: usize,
+}
+
+impl<'a, W> SliceInput<'a, W> {
+    pub fn new(values: &'a [W]) -> Self {
+        Self { values, consumed: 0 }
+    }
+
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    pub fn remaining(&self) -> &'a [W] {
+        &self.values[self.consumed..]
+    }
+}
+
+impl<'a, W: Clone> Input<W> for SliceInput<'a, W> {
+    fn next_input(&mut self) -> Option<W> {
+        let value = self.values.get(self.consumed).cloned()?;
+        self.consumed += 1;
+        Some(value)
+    }
+}
+
+/// Input computed on demand by a closure.
+pub struct FnInput<F>(pub F);
+
+impl<W, F: FnMut() -> Option<W>> Input<W> for FnInput<F> {
+    fn next_input(&mut self) -> Option<W> {
+        (self.0)()
+    }
+}
+
+/// Output handled by a closure.
+pub struct FnOutput<F>(pub F);
+
+impl<W, F: FnMut(W)> Output<W> for FnOutput<F> {
+    fn output(&mut self, value: W) {
+        (self.0)(value)
+    }
+}
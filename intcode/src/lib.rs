@@ -0,0 +1,47 @@
+//! The Intcode computer shared by every day that runs an Intcode program.
+//!
+//! This is the day 09 feature set: the relative base, and a machine that
+//! pauses (rather than panicking) when it runs out of input. Words are
+//! `i64` unless asked otherwise; `Program<W>` runs on any `Word`, from
+//! `i32` up to `BigInt`.
+
+pub mod aot;
+pub mod asm;
+pub mod bigint;
+mod budget;
+mod cache;
+pub mod cfg;
+pub mod debugger;
+pub mod device;
+pub mod disasm;
+mod error;
+pub mod executor;
+pub mod hook;
+mod instruction;
+pub mod io;
+pub mod load;
+mod memory;
+pub mod network;
+pub mod opcodes;
+pub mod profile;
+mod program;
+mod promote;
+pub mod record;
+mod snapshot;
+mod solver;
+pub mod symbolic;
+pub mod threaded;
+pub mod trace;
+mod word;
+
+pub use bigint::BigInt;
+pub use budget::Budget;
+pub use error::IntcodeError;
+pub use instruction::{decode_instruction, Instruction, Parameter, ParameterMode};
+pub use io::{Input, Output, StepResult};
+pub use load::parse;
+pub use memory::{Memory, DEFAULT_LIMIT, PAGE_SIZE};
+pub use program::{Overflow, Program, Step};
+pub use promote::Promoting;
+pub use snapshot::Snapshot;
+pub use word::Word;
@@ -0,0 +1,223 @@
+//! Reading programs from files or stdin, as text or as binary images.
+//!
+//! The text format is the usual comma-separated list of numbers. Whitespace
+//! (including newlines) around numbers and a trailing comma are fine; a
+//! malformed number is reported with its line and column.
+//!
+//! The binary image format is for large programs that would take a while to
+//! parse. It is little-endian:
+//!
+//! ```text
+//! magic     b"ICIM"
+//! version   u8 (1)
+//! count     u64
+//! words     count i64s
+//! ```
+
+use std::convert::TryInto;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::Word;
+
+const MAGIC: &[u8; 4] = b"ICIM";
+const VERSION: u8 = 1;
+
+/// A number in a text program that couldn't be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the start of the number.
+    pub line: usize,
+    /// 1-based column, in characters, of the start of the number.
+    pub column: usize,
+    /// The offending text, empty for a missing number.
+    pub word: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {:?}: {}", self.line, self.column, self.word, self.reason)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(e: ParseError) -> Self {
+        LoadError::Parse(e)
+    }
+}
+
+/// Parses a program in the comma-separated text format.
+pub fn parse<W: Word>(text: &str) -> Result<Vec<W>, ParseError>
+where
+    W::Err: fmt::Display,
+{
+    let mut words = vec![];
+    let mut offset = 0;
+    let mut rest = text;
+    loop {
+        let (word, next) = match rest.find(',') {
+            Some(comma) => (&rest[..comma], Some(&rest[comma + 1..])),
+            None => (rest, None),
+        };
+        let trimmed = word.trim();
+        let start = offset + (word.len() - word.trim_start().len());
+        match (trimmed.is_empty(), next) {
+            // Nothing at all, or nothing after a trailing comma.
+            (true, None) => break,
+            (true, _) => return Err(error(text, start, trimmed, "missing number".to_string())),
+            (false, _) => match trimmed.parse::<W>() {
+                Ok(value) => words.push(value),
+                Err(e) => return Err(error(text, start, trimmed, e.to_string())),
+            },
+        }
+        match next {
+            Some(next) => {
+                offset += word.len() + 1;
+                rest = next;
+            },
+            None => break,
+        }
+    }
+    Ok(words)
+}
+
+fn error(text: &str, offset: usize, word: &str, reason: String) -> ParseError {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    ParseError {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        word: word.to_string(),
+        reason,
+    }
+}
+
+/// Loads a program from `path`, or from stdin if `path` is `-`. Binary
+/// images are told apart from text by their magic number.
+pub fn load<W: Word>(path: impl AsRef<Path>) -> Result<Vec<W>, LoadError>
+where
+    W::Err: fmt::Display,
+{
+    let path = path.as_ref();
+    if path == Path::new("-") {
+        let stdin = io::stdin();
+        let lock = stdin.lock();
+        read(lock)
+    } else {
+        read(File::open(path)?)
+    }
+}
+
+/// Loads the program named by the first command-line argument, or else
+/// `default`. A relative `default` is looked for in the current directory
+/// and then next to the executable and in each directory above it, so a
+/// day's `src/input` is found wherever its binary is run from.
+pub fn from_args<W: Word>(default: &str) -> Result<Vec<W>, LoadError>
+where
+    W::Err: fmt::Display,
+{
+    match env::args().nth(1) {
+        Some(path) => load(path),
+        None => load(resolve(default)),
+    }
+}
+
+fn resolve(default: &str) -> PathBuf {
+    let default = Path::new(default);
+    if default.is_absolute() || default.exists() {
+        return default.to_path_buf();
+    }
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.ancestors().skip(1).map(|dir| dir.join(default)).find(|path| path.exists()))
+        .unwrap_or_else(|| default.to_path_buf())
+}
+
+/// Reads a whole program, text or binary image, from `reader`.
+pub fn read<W: Word>(mut reader: impl Read) -> Result<Vec<W>, LoadError>
+where
+    W::Err: fmt::Display,
+{
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    if bytes.starts_with(MAGIC) {
+        return Ok(read_image(&bytes[MAGIC.len()..])?);
+    }
+    let text = String::from_utf8(bytes).map_err(|_| invalid("neither text nor an Intcode image"))?;
+    Ok(parse(&text)?)
+}
+
+fn read_image<W: Word>(bytes: &[u8]) -> io::Result<Vec<W>> {
+    let (&version, bytes) = bytes.split_first().ok_or_else(|| invalid("truncated Intcode image"))?;
+    if version != VERSION {
+        return Err(invalid("unsupported image version"));
+    }
+    if bytes.len() < 8 {
+        return Err(invalid("truncated Intcode image"));
+    }
+    let (count, bytes) = bytes.split_at(8);
+    let count = u64::from_le_bytes(count.try_into().unwrap());
+    if bytes.len() as u64 != count.saturating_mul(8) {
+        return Err(invalid("Intcode image length doesn't match its word count"));
+    }
+    bytes.chunks_exact(8)
+        .map(|word| {
+            let word = i64::from_le_bytes(word.try_into().unwrap());
+            let value = W::from_i64(word);
+            if value.to_i64() == Some(word) { Ok(value) } else { Err(invalid("Intcode image word doesn't fit")) }
+        })
+        .collect()
+}
+
+/// Writes `image` in the binary image format. Every word has to fit in an
+/// `i64`.
+pub fn write_image<W: Word>(image: &[W], mut writer: impl Write) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    writer.write_all(&(image.len() as u64).to_le_bytes())?;
+    for word in image {
+        let word = word.to_i64().ok_or_else(|| invalid("word too large for an Intcode image"))?;
+        writer.write_all(&word.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub fn save_image<W: Word>(image: &[W], path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_image(image, &mut writer)?;
+    writer.flush()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
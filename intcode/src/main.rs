@@ -0,0 +1,215 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+use std::time::{Duration, Instant};
+
+use intcode::cfg::Cfg;
+use intcode::debugger::Debugger;
+use intcode::profile::Profile;
+use intcode::record::Recording;
+use intcode::symbolic::{Explorer, Solution};
+use intcode::trace::{JsonTrace, TextTrace};
+use intcode::{aot, asm, disasm, load, IntcodeError, Overflow, Program, Promoting};
+
+const USAGE: &str = "usage:
+    intcode aot [--name <module>] <program>
+    intcode asm <source>
+    intcode cfg <program>
+    intcode debug <program>
+    intcode disasm [--reachable] <program>
+    intcode image <program> <output>
+    intcode profile [--annotate] <program> [input...]
+    intcode run [--wrap | --promote] [--steps <n>] [--timeout <seconds>] [--detect-loops]
+                [--trace <file> [--json]] [--record <file>] <program> [input...]
+    intcode replay <program> <recording>
+    intcode symbolic <program> [output...]
+
+<program> is a text program or a binary image, or - for stdin.";
+
+fn main() {
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+
+    match args.first().map(String::as_str) {
+        Some("aot") => {
+            let name = take_option(&mut args, "--name").unwrap_or_else(|| "program".to_string());
+            let path = args.get(1).unwrap_or_else(|| usage());
+            print!("{}", aot::translate(&load(path), &name));
+        },
+        Some("asm") => {
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let source = read(path);
+            let image = asm::assemble(&source).unwrap_or_else(|e| fail(&format!("{}:{}", path, e)));
+            println!("{}", image.iter().map(i64::to_string).collect::<Vec<_>>().join(","));
+        },
+        Some("cfg") => {
+            let path = args.get(1).unwrap_or_else(|| usage());
+            print!("{}", Cfg::build(&load(path)).to_dot());
+        },
+        Some("debug") => {
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let mut debugger = Debugger::new(Program::init(load(path)));
+            let stdin = io::stdin();
+            debugger.repl(stdin.lock(), io::stdout()).unwrap_or_else(|e| fail(&e.to_string()));
+        },
+        Some("disasm") => {
+            let reachable = take_flag(&mut args, "--reachable");
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let image = load(path);
+            let lines = if reachable {
+                disasm::disassemble_reachable(&image)
+            } else {
+                disasm::disassemble(&image)
+            };
+            for line in lines {
+                println!("{}", line);
+            }
+        },
+        Some("image") => {
+            let (path, file) = match (args.get(1), args.get(2)) {
+                (Some(path), Some(file)) => (path, file),
+                _ => usage(),
+            };
+            load::save_image(&load(path), file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+        },
+        Some("profile") => {
+            let annotate = take_flag(&mut args, "--annotate");
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let input = args[2..].iter().map(|word| parse(path, word)).collect::<Vec<i64>>();
+            let image = load(path);
+            let mut profile = Profile::new();
+            let output = Program::init(image.clone()).execute_with(&input, &mut profile);
+            if annotate {
+                print!("{}", profile.annotate(&image));
+            } else {
+                print!("{}", profile.report(&image));
+            }
+            report(output);
+        },
+        Some("run") => {
+            let trace = take_option(&mut args, "--trace");
+            let json = take_flag(&mut args, "--json");
+            let wrap = take_flag(&mut args, "--wrap");
+            let promote = take_flag(&mut args, "--promote");
+            let steps = take_option(&mut args, "--steps");
+            let timeout = take_option(&mut args, "--timeout");
+            let detect_loops = take_flag(&mut args, "--detect-loops");
+            let record = take_option(&mut args, "--record");
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let input = args[2..].iter().map(|word| parse(path, word)).collect::<Vec<i64>>();
+            let mut program = Program::init(load(path));
+            if wrap {
+                program.overflow = Overflow::Wrap;
+            }
+            if let Some(steps) = steps {
+                program.budget.steps = Some(steps.parse().unwrap_or_else(|_| usage()));
+            }
+            if let Some(timeout) = timeout {
+                let seconds = timeout.parse().unwrap_or_else(|_| usage());
+                program.budget.deadline = Some(Instant::now() + Duration::from_secs_f64(seconds));
+            }
+            program.set_loop_detection(detect_loops);
+            if promote {
+                // Traces and recordings are of i64 words only.
+                if wrap || trace.is_some() || record.is_some() {
+                    usage();
+                }
+                match Promoting::new(program).execute(&input) {
+                    Ok(output) => println!("[{}]", output.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")),
+                    Err(e) => fail(&format!("error: {}", e)),
+                }
+                return;
+            }
+            let mut recording = record.as_ref().map(|_| Recording::new());
+            let output = match trace {
+                None => program.execute_with(&input, &mut recording),
+                Some(file) if json => {
+                    let mut trace = JsonTrace::create(&file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+                    let output = program.execute_with(&input, &mut (&mut trace, &mut recording));
+                    trace.finish().unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+                    output
+                },
+                Some(file) => {
+                    let mut trace = TextTrace::create(&file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+                    let output = program.execute_with(&input, &mut (&mut trace, &mut recording));
+                    trace.finish().unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+                    output
+                },
+            };
+            if let (Some(file), Some(recording)) = (record, recording) {
+                recording.save(&file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+            }
+            report(output);
+        },
+        Some("replay") => {
+            let (path, file) = match (args.get(1), args.get(2)) {
+                (Some(path), Some(file)) => (path, file),
+                _ => usage(),
+            };
+            let recording = Recording::load(file).unwrap_or_else(|e| fail(&format!("{}: {}", file, e)));
+            match recording.replay(&mut Program::init(load(path))) {
+                Ok(()) => println!("replayed {} steps", recording.steps),
+                Err(e) => fail(&format!("diverged at {}", e)),
+            }
+        },
+        Some("symbolic") => {
+            let path = args.get(1).unwrap_or_else(|| usage());
+            let expected = args[2..].iter().map(|word| parse(path, word)).collect::<Vec<i64>>();
+            for (i, found) in Explorer::default().explore(&load(path)).iter().enumerate() {
+                print!("path {}: {}", i, found);
+                match found.solve_outputs(&expected) {
+                    Solution::Sat(inputs) => println!("  inputs {:?}", inputs),
+                    Solution::Unsat => println!("  no inputs"),
+                    Solution::Unknown => println!("  unknown"),
+                }
+            }
+        },
+        _ => usage(),
+    }
+}
+
+fn report(output: Result<Vec<i64>, IntcodeError>) {
+    match output {
+        Ok(output) => println!("{:?}", output),
+        Err(e) => fail(&format!("error: {}", e)),
+    }
+}
+
+/// Removes `flag` from `args`, returning whether it was there.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != len
+}
+
+/// Removes `option` and the value after it from `args`.
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<String> {
+    let i = args.iter().position(|arg| arg == option)?;
+    if i + 1 >= args.len() {
+        usage();
+    }
+    args.remove(i);
+    Some(args.remove(i))
+}
+
+fn read(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)))
+}
+
+/// Loads a program, text or binary image, from a file or from stdin (`-`).
+fn load(path: &str) -> Vec<i64> {
+    load::load(path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)))
+}
+
+fn parse(path: &str, word: &str) -> i64 {
+    word.parse::<i64>().unwrap_or_else(|e| fail(&format!("{}: {:?}: {}", path, word, e)))
+}
+
+fn usage() -> ! {
+    fail(USAGE)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
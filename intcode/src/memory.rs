@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::ops::Index;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::Word;
+
+/// Number of words in one page of memory.
+pub const PAGE_SIZE: usize = 1024;
+
+/// The default address cap for dense memory, in words (128 MiB of `i64`s).
+pub const DEFAULT_LIMIT: usize = 1 << 24;
+
+pub(crate) type Page<W> = [W; PAGE_SIZE];
+
+fn zero_page<W: Word>() -> Arc<Page<W>> {
+    Arc::new(std::array::from_fn(|_| W::default()))
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Intcode memory. Reads past the end return zero and writes past the end
+/// grow the memory, up to `limit` addresses.
+///
+/// Memory is made of copy-on-write pages, so cloning it (and therefore
+/// forking a `Program`) only copies page pointers; a page is copied the
+/// first time one of the clones writes to it.
+///
+/// Dense memory keeps every page up to the highest address written, which
+/// is the right choice for every puzzle program so far. Sparse memory stores
+/// only the pages that have been written to, for programs that scatter
+/// writes across huge addresses.
+#[derive(Debug)]
+pub struct Memory<W = i64> {
+    cells: Cells<W>,
+    len: usize,
+    limit: usize,
+    version: Version,
+    /// What reads past the end borrow.
+    zero: W,
+}
+
+/// Identifies the contents of a memory: no two memories share an `id`,
+/// and `writes` goes up on every write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Version {
+    pub id: u64,
+    pub writes: u64,
+}
+
+impl Version {
+    fn new() -> Self {
+        Self { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), writes: 0 }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Cells<W> {
+    Dense(Vec<Arc<Page<W>>>),
+    Sparse(HashMap<usize, Arc<Page<W>>>),
+}
+
+impl<W: Word> Memory<W> {
+    pub fn dense(image: Vec<W>) -> Self {
+        let mut memory = Self {
+            len: 0,
+            cells: Cells::Dense(vec![]),
+            limit: DEFAULT_LIMIT,
+            version: Version::new(),
+            zero: W::default(),
+        };
+        memory.load(image);
+        memory
+    }
+
+    /// Sparse memory is uncapped by default since it only allocates pages
+    /// that are actually written.
+    pub fn sparse(image: Vec<W>) -> Self {
+        let mut memory = Self {
+            len: 0,
+            cells: Cells::Sparse(HashMap::new()),
+            limit: usize::MAX,
+            version: Version::new(),
+            zero: W::default(),
+        };
+        memory.load(image);
+        memory
+    }
+
+    fn load(&mut self, image: Vec<W>) {
+        let len = image.len();
+        for (address, value) in image.into_iter().enumerate() {
+            self.set(address, value);
+        }
+        self.len = len;
+    }
+
+    /// Sets the address cap. Addresses at or above `limit` are out of bounds.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        match self.cells {
+            Cells::Dense(_) => false,
+            Cells::Sparse(_) => true,
+        }
+    }
+
+    /// One past the highest address that has been initialized or written.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, address: usize) -> W {
+        self[address].clone()
+    }
+
+    /// Writes `value` at `address`, growing memory as needed. This does not
+    /// check the limit; `Program::write` does that.
+    pub fn set(&mut self, address: usize, value: W) {
+        let index = address / PAGE_SIZE;
+        let page = match &mut self.cells {
+            Cells::Dense(pages) => {
+                if index >= pages.len() {
+                    // Skipped pages all share one zero page until written.
+                    pages.resize(index + 1, zero_page());
+                }
+                &mut pages[index]
+            },
+            Cells::Sparse(pages) => pages.entry(index).or_insert_with(zero_page),
+        };
+        Arc::make_mut(page)[address % PAGE_SIZE] = value;
+        self.len = self.len.max(address + 1);
+        self.version.writes += 1;
+    }
+
+    /// Undoes growth, putting `len()` back to `len` once everything written
+    /// past it has been put back to zero.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    pub(crate) fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Copies the words in `start..end` out of memory.
+    pub fn range(&self, start: usize, end: usize) -> Vec<W> {
+        (start..end).map(|address| self[address].clone()).collect()
+    }
+
+    /// Copies memory into another word type, or returns `None` if some
+    /// word doesn't fit.
+    pub fn convert<V: Word>(&self) -> Option<Memory<V>> {
+        let mut memory = if self.is_sparse() { Memory::sparse(vec![]) } else { Memory::dense(vec![]) };
+        for (index, page) in self.pages() {
+            for (offset, word) in page.iter().enumerate().filter(|(_, word)| !word.is_zero()) {
+                memory.set(index * PAGE_SIZE + offset, word.to_string().parse().ok()?);
+            }
+        }
+        memory.len = self.len;
+        memory.limit = self.limit;
+        Some(memory)
+    }
+
+    /// Every allocated page with its index, in no particular order.
+    pub(crate) fn pages(&self) -> Vec<(usize, &Page<W>)> {
+        match &self.cells {
+            Cells::Dense(pages) => pages.iter().map(|page| &**page).enumerate().collect(),
+            Cells::Sparse(pages) => pages.iter().map(|(&index, page)| (index, &**page)).collect(),
+        }
+    }
+
+    /// Rebuilds memory from the parts returned by `pages`, `len` and `limit`.
+    /// Every page index must be below `len` pages, as `Snapshot::read_from`
+    /// checks.
+    pub(crate) fn from_pages(sparse: bool, len: usize, limit: usize, pages: Vec<(usize, Box<Page<W>>)>) -> Self {
+        let mut memory = if sparse { Memory::sparse(vec![]) } else { Memory::dense(vec![]) };
+        for (index, page) in pages {
+            let page = Arc::from(page);
+            match &mut memory.cells {
+                Cells::Dense(pages) => {
+                    if index >= pages.len() {
+                        pages.resize(index + 1, zero_page());
+                    }
+                    pages[index] = page;
+                },
+                Cells::Sparse(pages) => {
+                    pages.insert(index, page);
+                },
+            }
+        }
+        memory.len = len;
+        memory.limit = limit;
+        memory
+    }
+}
+
+impl<W: Clone> Clone for Memory<W> {
+    /// Clones share pages but get their own identity, since they are free
+    /// to diverge.
+    fn clone(&self) -> Self {
+        Self {
+            cells: self.cells.clone(),
+            len: self.len,
+            limit: self.limit,
+            version: Version::new(),
+            zero: self.zero.clone(),
+        }
+    }
+}
+
+impl<W: Word> From<Vec<W>> for Memory<W> {
+    fn from(image: Vec<W>) -> Self {
+        Memory::dense(image)
+    }
+}
+
+impl<W> Index<usize> for Memory<W> {
+    type Output = W;
+
+    fn index(&self, address: usize) -> &W {
+        let page = match &self.cells {
+            Cells::Dense(pages) => pages.get(address / PAGE_SIZE),
+            Cells::Sparse(pages) => pages.get(&(address / PAGE_SIZE)),
+        };
+        page.map_or(&self.zero, |page| &page[address % PAGE_SIZE])
+    }
+}
@@ -0,0 +1,169 @@
+//! Networks of Intcode machines wired together by named channels.
+//!
+//! Every node reads its input from one channel and copies each value it
+//! outputs to any number of channels. Chains, rings, fan-out (one node
+//! writing to several channels) and fan-in (several nodes writing to one
+//! channel) are all just different wirings.
+//!
+//! ```no_run
+//! # use intcode::{network::Network, Program};
+//! # let image = vec![99];
+//! // Day 07's feedback loop: five amplifiers in a ring.
+//! let mut network = Network::new();
+//! for (i, phase_setting) in [9, 8, 7, 6, 5].iter().enumerate() {
+//!     let input = format!("amp{}", i);
+//!     let output = format!("amp{}", (i + 1) % 5);
+//!     network.add_node(&input, Program::init(image.clone()), &input, &[&output]);
+//!     network.send(&input, *phase_setting);
+//! }
+//! network.send("amp0", 0);
+//! let report = network.run().unwrap();
+//! println!("{:?}", report.node("amp4").unwrap().outputs.last());
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use crate::{IntcodeError, Program, StepResult};
+
+struct Node {
+    name: String,
+    program: Program,
+    input: String,
+    outputs: Vec<String>,
+    sent: Vec<i64>,
+}
+
+#[derive(Default)]
+pub struct Network {
+    nodes: Vec<Node>,
+    channels: BTreeMap<String, VecDeque<i64>>,
+}
+
+/// How a network run ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Halted,
+    /// Every node still running is waiting on an empty channel.
+    Deadlock { blocked: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeReport {
+    pub name: String,
+    /// Every value the node has output, in order.
+    pub outputs: Vec<i64>,
+    pub halted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub status: Status,
+    pub nodes: Vec<NodeReport>,
+    /// Values sent but never read, by channel.
+    pub channels: BTreeMap<String, Vec<i64>>,
+}
+
+impl Report {
+    pub fn node(&self, name: &str) -> Option<&NodeReport> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkError {
+    pub node: String,
+    pub error: IntcodeError,
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.node, self.error)
+    }
+}
+
+impl Error for NetworkError {}
+
+impl Network {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node that reads from `input` and writes to every channel in
+    /// `outputs`. Nodes are scheduled in the order they are added.
+    pub fn add_node(&mut self, name: &str, program: Program, input: &str, outputs: &[&str]) {
+        assert!(self.nodes.iter().all(|node| node.name != name), "duplicate node {}", name);
+
+        self.channels.entry(input.to_string()).or_default();
+        for output in outputs {
+            self.channels.entry(output.to_string()).or_default();
+        }
+        self.nodes.push(Node {
+            name: name.to_string(),
+            program,
+            input: input.to_string(),
+            outputs: outputs.iter().map(|output| output.to_string()).collect(),
+            sent: vec![],
+        });
+    }
+
+    /// Queues `value` on `channel`, e.g. to seed a node with its settings.
+    pub fn send(&mut self, channel: &str, value: i64) {
+        self.channels.entry(channel.to_string()).or_default().push_back(value);
+    }
+
+    /// Runs the nodes round-robin, each until it blocks or halts, until
+    /// every node has halted or a whole round passes with no node reading
+    /// or writing anything.
+    pub fn run(&mut self) -> Result<Report, NetworkError> {
+        loop {
+            let mut progress = false;
+
+            for node in &mut self.nodes {
+                if node.program.halted {
+                    continue;
+                }
+
+                let input = self.channels.get_mut(&node.input).unwrap();
+                let waiting = input.len();
+                let mut output = vec![];
+                let result = node.program.run_io(input, &mut output)
+                    .map_err(|error| NetworkError { node: node.name.clone(), error })?;
+                progress |= input.len() != waiting || !output.is_empty() || result == StepResult::Halted;
+
+                for channel in &node.outputs {
+                    self.channels.get_mut(channel).unwrap().extend(&output);
+                }
+                node.sent.extend(output);
+            }
+
+            if self.nodes.iter().all(|node| node.program.halted) {
+                return Ok(self.report(Status::Halted));
+            }
+            if !progress {
+                let blocked = self.nodes.iter()
+                    .filter(|node| !node.program.halted)
+                    .map(|node| node.name.clone())
+                    .collect();
+                return Ok(self.report(Status::Deadlock { blocked }));
+            }
+        }
+    }
+
+    fn report(&self, status: Status) -> Report {
+        Report {
+            status,
+            nodes: self.nodes.iter()
+                .map(|node| NodeReport {
+                    name: node.name.clone(),
+                    outputs: node.sent.clone(),
+                    halted: node.program.halted,
+                })
+                .collect(),
+            channels: self.channels.iter()
+                .map(|(name, values)| (name.clone(), values.iter().copied().collect()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Registries of opcodes, for machines with instructions of their own.
+//!
+//! A `Program` runs the standard instruction set unless given an `Opcodes`
+//! registry. Starting from the standard set, opcodes can be added,
+//! replaced or taken away, e.g. a debug print for teaching:
+//!
+//! ```
+//! # use intcode::opcodes::{Opcodes, Outcome};
+//! # use intcode::Program;
+//! let mut opcodes = Opcodes::standard();
+//! opcodes.register(10, "DBG", 1, 0, |call| {
+//!     eprintln!("{:04}: {}", call.i_ptr, call.operands[0]);
+//!     Ok(Outcome::default())
+//! });
+//!
+//! let mut program = Program::init(vec![1010, 42, 99]);
+//! program.set_opcodes(opcodes);
+//! assert_eq!(program.execute(&[]), Ok(vec![]));
+//! ```
+//!
+//! Custom instructions take at most three parameters, and write at most
+//! one of them, like the standard ones; that way hooks see everything an
+//! instruction did in its `Effects`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::instruction::decode_parameter;
+use crate::{decode_instruction, Instruction, IntcodeError, Memory, Parameter, ParameterMode, Word};
+
+/// The most parameters an instruction can have.
+pub const MAX_ARITY: usize = 3;
+
+/// What a custom instruction's handler gets to look at.
+pub struct Call<'a, W = i64> {
+    pub i_ptr: usize,
+    pub relative_base: &'a W,
+    /// Resolved operands, as in `hook::Event`: the value read for inputs,
+    /// the address for the parameter written to.
+    pub operands: &'a [W],
+    pub memory: &'a Memory<W>,
+}
+
+/// What a custom instruction did. The default is nothing: carry on with
+/// the next instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome<W = i64> {
+    /// The value for the write parameter. Ignored if there isn't one.
+    pub write: Option<W>,
+    pub output: Option<W>,
+    /// Where to go next, instead of the following instruction.
+    pub jump: Option<usize>,
+    /// Stop the machine, leaving it on this instruction. Takes precedence
+    /// over `output` and `jump`.
+    pub halt: bool,
+}
+
+impl<W> Default for Outcome<W> {
+    fn default() -> Self {
+        Self { write: None, output: None, jump: None, halt: false }
+    }
+}
+
+pub type Handler<W> = dyn Fn(&Call<W>) -> Result<Outcome<W>, IntcodeError<W>> + Send + Sync;
+
+#[derive(Clone)]
+pub struct Definition<W = i64> {
+    pub mnemonic: &'static str,
+    pub arity: usize,
+    /// Bit `i` is set if parameter `i` is written to.
+    pub writes: u8,
+    /// `None` for the standard instruction the opcode normally means.
+    handler: Option<Arc<Handler<W>>>,
+}
+
+impl<W> Definition<W> {
+    pub fn is_standard(&self) -> bool {
+        self.handler.is_none()
+    }
+
+    pub(crate) fn handler(&self) -> Option<&Handler<W>> {
+        self.handler.as_deref()
+    }
+}
+
+/// Opcodes (the last two digits of an instruction's first word) and what
+/// they mean.
+#[derive(Clone)]
+pub struct Opcodes<W = i64> {
+    definitions: BTreeMap<i64, Definition<W>>,
+}
+
+impl<W: Word> Opcodes<W> {
+    /// No opcodes at all.
+    pub fn empty() -> Self {
+        Self { definitions: BTreeMap::new() }
+    }
+
+    /// The standard instruction set, 1 to 9 and 99.
+    pub fn standard() -> Self {
+        let mut opcodes = Self::empty();
+        let standard = [
+            (1, "ADD", 3, 0b100),
+            (2, "MUL", 3, 0b100),
+            (3, "IN", 1, 0b1),
+            (4, "OUT", 1, 0),
+            (5, "JNZ", 2, 0),
+            (6, "JZ", 2, 0),
+            (7, "LT", 3, 0b100),
+            (8, "EQ", 3, 0b100),
+            (9, "ARB", 1, 0),
+            (99, "HLT", 0, 0),
+        ];
+        for &(opcode, mnemonic, arity, writes) in &standard {
+            opcodes.definitions.insert(opcode, Definition { mnemonic, arity, writes, handler: None });
+        }
+        opcodes
+    }
+
+    /// Defines `opcode` (0 to 99), replacing whatever it meant before.
+    ///
+    /// Panics if `arity` is over `MAX_ARITY` or `writes` doesn't name at
+    /// most one of the parameters.
+    pub fn register(
+        &mut self,
+        opcode: i64,
+        mnemonic: &'static str,
+        arity: usize,
+        writes: u8,
+        handler: impl Fn(&Call<W>) -> Result<Outcome<W>, IntcodeError<W>> + Send + Sync + 'static,
+    ) -> &mut Self {
+        assert!((0..100).contains(&opcode), "opcode {} is not two digits", opcode);
+        assert!(arity <= MAX_ARITY, "{} has more than {} parameters", mnemonic, MAX_ARITY);
+        assert!(writes >> arity == 0 && writes.count_ones() <= 1, "{} writes to more than one parameter", mnemonic);
+        let handler: Arc<Handler<W>> = Arc::new(handler);
+        self.definitions.insert(opcode, Definition { mnemonic, arity, writes, handler: Some(handler) });
+        self
+    }
+
+    /// Takes `opcode` out of the instruction set, so that running into it
+    /// is an `UnknownOpcode` error.
+    pub fn remove(&mut self, opcode: i64) -> &mut Self {
+        self.definitions.remove(&opcode);
+        self
+    }
+
+    /// Whether any opcode runs a handler rather than a standard
+    /// instruction.
+    pub fn has_handlers(&self) -> bool {
+        self.definitions.values().any(|definition| !definition.is_standard())
+    }
+
+    pub fn get(&self, opcode: i64) -> Option<&Definition<W>> {
+        self.definitions.get(&opcode)
+    }
+
+    /// Decodes the instruction starting at `i_ptr` in this instruction set.
+    pub fn decode(&self, memory: &Memory<W>, i_ptr: usize) -> Result<Instruction<W>, IntcodeError<W>> {
+        let unknown = || IntcodeError::UnknownOpcode { addr: i_ptr, value: memory[i_ptr].clone() };
+        let opcode = match memory[i_ptr].to_i64() {
+            Some(opcode) if opcode >= 0 => opcode,
+            _ => return Err(unknown()),
+        };
+        let definition = self.definitions.get(&(opcode % 100)).ok_or_else(unknown)?;
+        if definition.is_standard() {
+            return decode_instruction(memory, i_ptr);
+        }
+
+        let unused = || Parameter { mode: ParameterMode::Position, value: W::default() };
+        let mut parameters = [unused(), unused(), unused()];
+        for (i, parameter) in parameters.iter_mut().enumerate().take(definition.arity) {
+            *parameter = decode_parameter(memory, i_ptr, opcode, i)?;
+        }
+        Ok(Instruction::Custom {
+            opcode: opcode % 100,
+            mnemonic: definition.mnemonic,
+            arity: definition.arity as u8,
+            writes: definition.writes,
+            parameters,
+        })
+    }
+}
+
+impl<W: Word> Default for Opcodes<W> {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl<W> fmt::Debug for Opcodes<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.definitions.iter().map(|(opcode, definition)| (opcode, definition.mnemonic)))
+            .finish()
+    }
+}
@@ -0,0 +1,201 @@
+//! Profiling and coverage for Intcode runs.
+//!
+//! `Profile` is a hook, so profiling a run is just running it with one:
+//!
+//! ```
+//! # use intcode::{profile::Profile, Program};
+//! let image = vec![1101, 2, 3, 5, 99, 0];
+//! let mut profile = Profile::new();
+//! Program::init(image.clone()).execute_with(&[], &mut profile).unwrap();
+//! assert_eq!(profile.steps, 2);
+//! print!("{}", profile.report(&image));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ptr;
+
+use crate::cfg::{Cfg, RegionKind};
+use crate::disasm::{decode_fitting, sweep};
+use crate::hook::{Event, Hook};
+use crate::{Instruction, Memory, ParameterMode};
+
+/// How many rows the report's ranked tables show.
+const TOP: usize = 10;
+
+/// Counts of everything a run did, gathered one step at a time.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub steps: u64,
+    /// Executions per instruction address.
+    pub executions: BTreeMap<usize, u64>,
+    /// Executions per mnemonic.
+    pub opcodes: BTreeMap<&'static str, u64>,
+    /// Reads per address, by instruction parameters.
+    pub reads: BTreeMap<usize, u64>,
+    /// Writes per address.
+    pub writes: BTreeMap<usize, u64>,
+    /// Targets of backward jumps taken, with how often each was taken to.
+    pub loop_headers: BTreeMap<usize, u64>,
+    /// The instruction last executed at each address.
+    instructions: BTreeMap<usize, Instruction>,
+}
+
+impl Hook for Profile {
+    fn on_step(&mut self, event: &Event) {
+        let instruction = event.instruction;
+        self.steps += 1;
+        *self.executions.entry(event.i_ptr).or_insert(0) += 1;
+        *self.opcodes.entry(instruction.mnemonic()).or_insert(0) += 1;
+        self.instructions.insert(event.i_ptr, *instruction);
+
+        let written = instruction.write_parameter();
+        for parameter in instruction.parameters() {
+            if written.is_some_and(|written| ptr::eq(written, parameter)) {
+                continue;
+            }
+            let address = match parameter.mode {
+                ParameterMode::Immediate => continue,
+                ParameterMode::Position => parameter.value,
+                ParameterMode::Relative => parameter.value + event.relative_base,
+            };
+            *self.reads.entry(address as usize).or_insert(0) += 1;
+        }
+        if let Some(write) = event.effects.write {
+            *self.writes.entry(write.address).or_insert(0) += 1;
+        }
+        if let Some(target) = event.effects.jump {
+            if target <= event.i_ptr {
+                *self.loop_headers.entry(target).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `n` most executed addresses, most executed first.
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut hottest = self.executions.iter().map(|(&address, &count)| (address, count)).collect::<Vec<_>>();
+        hottest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hottest.truncate(n);
+        hottest
+    }
+
+    /// Runs of words, as `start..end`, that the control-flow graph of
+    /// `image` takes for code (reachable or dead) but that no executed
+    /// instruction covered.
+    pub fn untouched(&self, image: &[i64]) -> Vec<(usize, usize)> {
+        let covered = self.covered();
+        let mut untouched: Vec<(usize, usize)> = vec![];
+        for region in Cfg::build(image).regions {
+            if region.kind == RegionKind::Data {
+                continue;
+            }
+            for address in (region.start..region.end).filter(|address| !covered.contains(address)) {
+                match untouched.last_mut() {
+                    Some((_, end)) if *end == address => *end += 1,
+                    _ => untouched.push((address, address + 1)),
+                }
+            }
+        }
+        untouched
+    }
+
+    /// Every word of every executed instruction.
+    fn covered(&self) -> BTreeSet<usize> {
+        self.instructions.iter()
+            .flat_map(|(&address, instruction)| address..address + instruction.size())
+            .collect()
+    }
+
+    /// A plain text report: hottest addresses, instruction mix, loop
+    /// headers, memory traffic and untouched code.
+    pub fn report(&self, image: &[i64]) -> String {
+        let percent = |count: u64| 100.0 * count as f64 / self.steps.max(1) as f64;
+        let mut out = String::new();
+        writeln!(out, "{} steps, {} distinct instructions", self.steps, self.executions.len()).unwrap();
+
+        writeln!(out, "\nhottest addresses").unwrap();
+        writeln!(out, "  address      count       %  instruction").unwrap();
+        for (address, count) in self.hottest(TOP) {
+            let instruction = self.instructions[&address];
+            writeln!(out, "  {:>7}  {:>9}  {:>5.1}%  {}", address, count, percent(count), instruction).unwrap();
+        }
+
+        writeln!(out, "\ninstruction mix").unwrap();
+        let mut mix = self.opcodes.iter().collect::<Vec<_>>();
+        mix.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (mnemonic, &count) in mix {
+            writeln!(out, "  {:<7}  {:>9}  {:>5.1}%", mnemonic, count, percent(count)).unwrap();
+        }
+
+        writeln!(out, "\nloop headers").unwrap();
+        writeln!(out, "  address  back-jumps").unwrap();
+        for (address, count) in &self.loop_headers {
+            writeln!(out, "  {:>7}  {:>10}", address, count).unwrap();
+        }
+
+        writeln!(out, "\nmemory: {} addresses read, {} written", self.reads.len(), self.writes.len()).unwrap();
+        writeln!(out, "  address      reads     writes").unwrap();
+        let mut traffic = self.reads.keys().chain(self.writes.keys()).copied().collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|address| (address, self.count(&self.reads, address), self.count(&self.writes, address)))
+            .collect::<Vec<_>>();
+        traffic.sort_by(|a, b| (b.1 + b.2).cmp(&(a.1 + a.2)).then(a.0.cmp(&b.0)));
+        for &(address, reads, writes) in traffic.iter().take(TOP) {
+            writeln!(out, "  {:>7}  {:>9}  {:>9}", address, reads, writes).unwrap();
+        }
+
+        writeln!(out, "\nuntouched code").unwrap();
+        for (start, end) in self.untouched(image) {
+            writeln!(out, "  {:04}-{:04}  ({} words)", start, end - 1, end - start).unwrap();
+        }
+        out
+    }
+
+    /// Disassembles `image`, aligned to the instructions that actually ran,
+    /// with each line prefixed by how often it executed (`-` for code that
+    /// never did) and the reads and writes to its words.
+    pub fn annotate(&self, image: &[i64]) -> String {
+        let memory = Memory::dense(image.to_vec());
+        let covered = self.covered();
+        let lines = sweep(&memory, 0, image.len(), |address| {
+            if self.executions.contains_key(&address) {
+                return decode_fitting(&memory, image.len(), address);
+            }
+            // Don't let code that never ran swallow the start of code that
+            // did.
+            decode_fitting(&memory, image.len(), address).filter(|instruction| {
+                (address..address + instruction.size()).all(|word| !covered.contains(&word))
+            })
+        });
+
+        let mut out = String::new();
+        for line in lines {
+            let count = match (self.executions.get(&line.address), &line.instruction) {
+                (Some(count), _) => count.to_string(),
+                (None, Some(_)) => "-".to_string(),
+                (None, None) => String::new(),
+            };
+            let words = line.address..line.address + line.words.len();
+            let reads = words.clone().map(|address| self.count(&self.reads, address)).sum::<u64>();
+            let writes = words.map(|address| self.count(&self.writes, address)).sum::<u64>();
+            let traffic = match (reads, writes) {
+                (0, 0) => String::new(),
+                (reads, 0) => format!("r{}", reads),
+                (0, writes) => format!("w{}", writes),
+                (reads, writes) => format!("r{} w{}", reads, writes),
+            };
+            writeln!(out, "{:>9} {:>15}  {}", count, traffic, line).unwrap();
+        }
+        out
+    }
+
+    fn count(&self, counts: &BTreeMap<usize, u64>, address: usize) -> u64 {
+        counts.get(&address).copied().unwrap_or(0)
+    }
+}
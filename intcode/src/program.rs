@@ -0,0 +1,478 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::hook::{Effects, Event, Hook, MemoryWrite};
+use crate::io::{Input, Output, SliceInput, StepResult};
+use crate::budget::{Budget, LoopDetector, DEADLINE_INTERVAL};
+use crate::cache::DecodeCache;
+use crate::device::Device;
+use crate::opcodes::{Call, Opcodes};
+use crate::{Instruction, IntcodeError, Memory, Word};
+
+/// What happens when an `ADD`, a `MUL` or a relative base calculation
+/// doesn't fit in a word.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Overflow {
+    /// Wrap around, two's complement.
+    Wrap,
+    /// Stop with `IntcodeError::Overflow`, leaving the machine on the
+    /// instruction that overflowed.
+    #[default]
+    Trap,
+    /// Stop as for `Trap`, for a `Promoting` machine to carry on from in a
+    /// wider word.
+    Promote,
+}
+
+/// The outcome of executing a single instruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step<W = i64> {
+    Ran,
+    Output(W),
+    NeedsInput,
+    Halted,
+}
+
+#[derive(Debug, Clone)]
+pub struct Program<W = i64> {
+    pub memory: Memory<W>,
+    pub halted: bool,
+    pub i_ptr: usize,
+    pub relative_base: W,
+    pub overflow: Overflow,
+    pub budget: Budget,
+    cache: DecodeCache<W>,
+    /// The instruction set, if not the standard one.
+    opcodes: Option<Arc<Opcodes<W>>>,
+    devices: Vec<Mapping<W>>,
+    loops: Option<LoopDetector<W>>,
+    /// Instructions executed, for timing deadline checks.
+    ticks: u32,
+}
+
+impl<W: Word> Program<W> {
+
+    pub fn init(memory: Vec<W>) -> Self {
+        Self::with_memory(Memory::dense(memory))
+    }
+
+    /// Starts a program on an already configured memory, e.g. sparse or
+    /// with a custom limit.
+    pub fn with_memory(memory: Memory<W>) -> Self {
+        Self {
+            memory,
+            halted: false,
+            i_ptr: 0,
+            relative_base: W::default(),
+            overflow: Overflow::default(),
+            budget: Budget::default(),
+            cache: DecodeCache::new(),
+            opcodes: None,
+            devices: vec![],
+            loops: None,
+            ticks: 0,
+        }
+    }
+
+    /// Turns the cache of decoded instructions on or off. It is on by
+    /// default; turning it off is only useful for measuring what it buys.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.cache.enabled = enabled;
+    }
+
+    /// Runs the program on another instruction set from now on.
+    pub fn set_opcodes(&mut self, opcodes: Opcodes<W>) {
+        self.opcodes = Some(Arc::new(opcodes));
+        self.cache.clear();
+    }
+
+    /// Turns on or off stopping with `IntcodeError::InfiniteLoop` when the
+    /// program provably spins forever without I/O. It is off by default,
+    /// as it slows every instruction down. It has nothing to say about
+    /// programs with devices attached, which may answer differently every
+    /// time they are read, or with custom instructions, whose handlers may
+    /// keep state of their own.
+    pub fn set_loop_detection(&mut self, enabled: bool) {
+        self.loops = if enabled { Some(LoopDetector::new()) } else { None };
+    }
+
+    /// Whether a budget or the loop detector is watching the run, or the
+    /// program runs on a custom instruction set or has devices attached.
+    pub fn is_limited(&self) -> bool {
+        self.budget != Budget::default() || self.loops.is_some() || self.opcodes.is_some() || !self.devices.is_empty()
+    }
+
+    /// Copies the machine, mid-run or not, into another word type, or
+    /// returns `None` if some word doesn't fit, or the program runs on a
+    /// custom instruction set or has devices attached, which only know `W`.
+    /// After an
+    /// `IntcodeError::Overflow` this is how to carry on in a wider word:
+    /// nothing was written, so the wider machine retries the instruction.
+    pub fn convert<V: Word>(&self) -> Option<Program<V>> {
+        if self.opcodes.is_some() || !self.devices.is_empty() {
+            return None;
+        }
+        Some(Program {
+            memory: self.memory.convert()?,
+            halted: self.halted,
+            i_ptr: self.i_ptr,
+            relative_base: self.relative_base.to_string().parse().ok()?,
+            overflow: self.overflow,
+            budget: self.budget,
+            cache: DecodeCache::new(),
+            opcodes: None,
+            devices: vec![],
+            loops: self.loops.as_ref().map(|_| LoopDetector::new()),
+            ticks: self.ticks,
+        })
+    }
+
+    /// `a + b` under the overflow policy.
+    pub fn add(&self, a: W, b: W) -> Result<W, IntcodeError<W>> {
+        match self.overflow {
+            Overflow::Wrap => Ok(a.wrapping_add(&b)),
+            Overflow::Trap | Overflow::Promote => a.checked_add(&b).ok_or(IntcodeError::Overflow { addr: self.i_ptr }),
+        }
+    }
+
+    /// `a * b` under the overflow policy.
+    pub fn mul(&self, a: W, b: W) -> Result<W, IntcodeError<W>> {
+        match self.overflow {
+            Overflow::Wrap => Ok(a.wrapping_mul(&b)),
+            Overflow::Trap | Overflow::Promote => a.checked_mul(&b).ok_or(IntcodeError::Overflow { addr: self.i_ptr }),
+        }
+    }
+
+    /// Checks that `address` can be used to index memory.
+    pub fn address(&self, address: W) -> Result<usize, IntcodeError<W>> {
+        if address.is_negative() {
+            return Err(IntcodeError::NegativeAddress { addr: self.i_ptr, address });
+        }
+        // Anything too big for a `usize` is past any memory limit anyway.
+        match address.to_i64() {
+            Some(address) if address as u64 <= usize::MAX as u64 => Ok(address as usize),
+            _ => Err(IntcodeError::OutOfBounds { addr: self.i_ptr, address: usize::MAX }),
+        }
+    }
+
+    /// Reads memory, failing only for addresses past the memory limit.
+    pub fn read(&self, address: usize) -> Result<W, IntcodeError<W>> {
+        self.check_limit(address)?;
+        match self.device_at(address) {
+            Some((mapping, offset)) => Ok(mapping.device.lock().unwrap().read(offset)),
+            None => Ok(self.memory.get(address)),
+        }
+    }
+
+    /// Writes memory, growing it if needed, up to the memory limit.
+    pub fn write(&mut self, address: usize, value: W) -> Result<(), IntcodeError<W>> {
+        self.check_limit(address)?;
+        if let Some((mapping, offset)) = self.device_at(address) {
+            mapping.device.lock().unwrap().write(offset, value);
+            return Ok(());
+        }
+        self.memory.set(address, value);
+        self.cache.invalidate(&self.memory, address);
+        Ok(())
+    }
+
+    /// Maps `device` over `start..start + len`, returning a handle to it.
+    /// Clones of the program share the device.
+    ///
+    /// Panics if the range overlaps another device's or goes past the
+    /// memory limit.
+    pub fn attach<D: Device<W> + 'static>(&mut self, start: usize, len: usize, device: D) -> Arc<Mutex<D>> {
+        let range = start..start + len;
+        assert!(range.end <= self.memory.limit(), "device at {} past the memory limit", start);
+        assert!(
+            self.devices.iter().all(|mapping| mapping.range.end <= range.start || range.end <= mapping.range.start),
+            "device at {} overlaps another", start,
+        );
+        let device = Arc::new(Mutex::new(device));
+        self.devices.push(Mapping { range, device: device.clone() });
+        device
+    }
+
+    /// Removes the device attached at `start`, returning whether there was
+    /// one.
+    pub fn detach(&mut self, start: usize) -> bool {
+        let len = self.devices.len();
+        self.devices.retain(|mapping| mapping.range.start != start);
+        self.devices.len() != len
+    }
+
+    fn device_at(&self, address: usize) -> Option<(&Mapping<W>, usize)> {
+        self.devices.iter()
+            .find(|mapping| mapping.range.contains(&address))
+            .map(|mapping| (mapping, address - mapping.range.start))
+    }
+
+    /// Fails if `address` is past the memory limit, without touching it
+    /// (a device there might notice).
+    pub fn check_limit(&self, address: usize) -> Result<(), IntcodeError<W>> {
+        if address >= self.memory.limit() {
+            Err(IntcodeError::OutOfBounds { addr: self.i_ptr, address })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Runs the program until it halts or needs more input than was given,
+    /// returning everything it output along the way. A program paused on
+    /// input resumes from the same `In` on the next call. Input left over
+    /// when the program halts is dropped; use `run` to know what was used.
+    ///
+    /// On error the machine is left pointing at the failed instruction.
+    pub fn execute(&mut self, input: &[W]) -> Result<Vec<W>, IntcodeError<W>> {
+        self.execute_with(input, &mut ())
+    }
+
+    /// `execute`, calling `hook` after every instruction.
+    pub fn execute_with<H: Hook<W> + ?Sized>(&mut self, input: &[W], hook: &mut H) -> Result<Vec<W>, IntcodeError<W>> {
+        let mut input = SliceInput::new(input);
+        let mut output = vec![];
+
+        loop {
+            match self.run_with(&mut input, hook)? {
+                StepResult::Output(val) => output.push(val),
+                StepResult::NeedsInput | StepResult::Halted => break Ok(output),
+            }
+        }
+    }
+
+    /// Runs until the next event: an output, an `In` that `input` has no
+    /// value for, or a halt. Values are only taken from `input` as `In`s
+    /// execute, so whatever is left in it afterwards was not consumed.
+    pub fn run(&mut self, input: &mut impl Input<W>) -> Result<StepResult<W>, IntcodeError<W>> {
+        self.run_with(input, &mut ())
+    }
+
+    /// `run`, calling `hook` after every instruction.
+    pub fn run_with<H: Hook<W> + ?Sized>(&mut self, input: &mut impl Input<W>, hook: &mut H) -> Result<StepResult<W>, IntcodeError<W>> {
+        loop {
+            match self.step_with(input, hook)? {
+                Step::Ran => {},
+                Step::Output(val) => break Ok(StepResult::Output(val)),
+                Step::NeedsInput => break Ok(StepResult::NeedsInput),
+                Step::Halted => break Ok(StepResult::Halted),
+            }
+        }
+    }
+
+    /// Runs until the program halts or blocks on input, sending every value
+    /// it outputs to `output`. Returns `NeedsInput` or `Halted`.
+    pub fn run_io(&mut self, input: &mut impl Input<W>, output: &mut impl Output<W>) -> Result<StepResult<W>, IntcodeError<W>> {
+        loop {
+            match self.run(input)? {
+                StepResult::Output(val) => output.output(val),
+                result => break Ok(result),
+            }
+        }
+    }
+
+    /// Executes a single instruction, taking a value from `input` if it is
+    /// an `In`. Neither a halted program nor one waiting on input moves.
+    pub fn step(&mut self, input: &mut impl Input<W>) -> Result<Step<W>, IntcodeError<W>> {
+        self.step_with(input, &mut ())
+    }
+
+    /// `step`, calling `hook` if an instruction was executed. A `Halt` is
+    /// reported every time it is reached; an `In` waiting on input is not.
+    pub fn step_with<H: Hook<W> + ?Sized>(&mut self, input: &mut impl Input<W>, hook: &mut H) -> Result<Step<W>, IntcodeError<W>> {
+        let i_ptr = self.i_ptr;
+        let relative_base = self.relative_base.clone();
+        let instruction = self.cache.decode(&self.memory, i_ptr, self.opcodes.as_deref())?;
+        if !matches!(instruction, Instruction::Halt) {
+            self.check_budget()?;
+            let predictable = self.devices.is_empty() && self.opcodes.as_ref().is_none_or(|opcodes| !opcodes.has_handlers());
+            if let Some(loops) = self.loops.as_mut().filter(|_| predictable) {
+                if loops.repeats(i_ptr, &self.relative_base, self.memory.version()) {
+                    return Err(IntcodeError::InfiniteLoop { addr: i_ptr });
+                }
+            }
+        }
+        let mut operands = [W::default(), W::default(), W::default()];
+        let mut effects = Effects::default();
+        let mut step = Step::Ran;
+
+        use Instruction::*;
+        match &instruction {
+            Halt => {
+                self.halted = true;
+                step = Step::Halted;
+            },
+            Add(p1, p2, p3) => {
+                let store = p3.get_value_write(self)?;
+                operands = [p1.get_value(self)?, p2.get_value(self)?, W::from_i64(store as i64)];
+                let val = self.add(operands[0].clone(), operands[1].clone())?;
+                effects.write = Some(self.write_traced(store, val)?);
+
+                self.i_ptr += 4;
+            },
+            Mul(p1, p2, p3) => {
+                let store = p3.get_value_write(self)?;
+                operands = [p1.get_value(self)?, p2.get_value(self)?, W::from_i64(store as i64)];
+                let val = self.mul(operands[0].clone(), operands[1].clone())?;
+                effects.write = Some(self.write_traced(store, val)?);
+
+                self.i_ptr += 4;
+            },
+            In(p1) => {
+                let store = p1.get_value_write(self)?;
+                self.check_limit(store)?;
+                let val = match input.next_input() {
+                    Some(val) => val,
+                    None => {
+                        // Waiting isn't looping: the state may well repeat
+                        // once input arrives.
+                        if let Some(loops) = &mut self.loops {
+                            loops.reset();
+                        }
+                        return Ok(Step::NeedsInput);
+                    },
+                };
+                operands[0] = W::from_i64(store as i64);
+                effects.input = Some(val.clone());
+                effects.write = Some(self.write_traced(store, val)?);
+
+                self.i_ptr += 2;
+            },
+            Out(p1) => {
+                let val = p1.get_value(self)?;
+                operands[0] = val.clone();
+                effects.output = Some(val.clone());
+
+                self.i_ptr += 2;
+                step = Step::Output(val);
+            },
+            Jnz(p1, p2) => {
+                operands = [p1.get_value(self)?, p2.get_value(self)?, W::default()];
+                if !operands[0].is_zero() {
+                    self.i_ptr = self.address(operands[1].clone())?;
+                    effects.jump = Some(self.i_ptr);
+                } else {
+                    self.i_ptr += 3;
+                }
+            },
+            Jz(p1, p2) => {
+                operands = [p1.get_value(self)?, p2.get_value(self)?, W::default()];
+                if operands[0].is_zero() {
+                    self.i_ptr = self.address(operands[1].clone())?;
+                    effects.jump = Some(self.i_ptr);
+                } else {
+                    self.i_ptr += 3;
+                }
+            },
+            Lt(p1, p2, p3) => {
+                let store = p3.get_value_write(self)?;
+                operands = [p1.get_value(self)?, p2.get_value(self)?, W::from_i64(store as i64)];
+                let val = W::from_i64(if operands[0] < operands[1] { 1 } else { 0 });
+                effects.write = Some(self.write_traced(store, val)?);
+
+                self.i_ptr += 4;
+            },
+            Eq(p1, p2, p3) => {
+                let store = p3.get_value_write(self)?;
+                operands = [p1.get_value(self)?, p2.get_value(self)?, W::from_i64(store as i64)];
+                let val = W::from_i64(if operands[0] == operands[1] { 1 } else { 0 });
+                effects.write = Some(self.write_traced(store, val)?);
+
+                self.i_ptr += 4;
+            },
+            ChangeBase(p1) => {
+                operands[0] = p1.get_value(self)?;
+                self.relative_base = self.add(self.relative_base.clone(), operands[0].clone())?;
+                effects.relative_base = Some(self.relative_base.clone());
+
+                self.i_ptr += 2;
+            },
+            Custom { opcode, arity, parameters, .. } => {
+                let arity = *arity as usize;
+                let write = instruction.write_parameter();
+                for (i, parameter) in parameters.iter().enumerate().take(arity) {
+                    operands[i] = match write {
+                        Some(written) if std::ptr::eq(written, parameter) => {
+                            W::from_i64(parameter.get_value_write(self)? as i64)
+                        },
+                        _ => parameter.get_value(self)?,
+                    };
+                }
+                let opcodes = self.opcodes.clone().expect("custom instruction without opcodes");
+                let handler = opcodes.get(*opcode).and_then(|definition| definition.handler())
+                    .expect("custom instruction without a handler");
+                let outcome = handler(&Call {
+                    i_ptr,
+                    relative_base: &self.relative_base,
+                    operands: &operands[..arity],
+                    memory: &self.memory,
+                })?;
+
+                if let (Some(parameter), Some(value)) = (write, outcome.write) {
+                    let store = parameter.get_value_write(self)?;
+                    effects.write = Some(self.write_traced(store, value)?);
+                }
+                if let Some(value) = outcome.output {
+                    effects.output = Some(value.clone());
+                    step = Step::Output(value);
+                }
+                if outcome.halt {
+                    self.halted = true;
+                    step = Step::Halted;
+                } else if let Some(target) = outcome.jump {
+                    self.i_ptr = target;
+                    effects.jump = Some(target);
+                } else {
+                    self.i_ptr += 1 + arity;
+                }
+            },
+        }
+
+        // A `Halt` isn't held to the budget, since it is reached again every
+        // step once halted, so it doesn't spend any of it either.
+        if let Some(steps) = self.budget.steps.as_mut().filter(|_| !matches!(instruction, Halt)) {
+            *steps -= 1;
+        }
+        self.ticks = self.ticks.wrapping_add(1);
+        if let Some(loops) = &mut self.loops {
+            loops.record(&effects, self.memory.version());
+        }
+
+        hook.on_step(&Event { i_ptr, relative_base, instruction: &instruction, operands, effects });
+        Ok(step)
+    }
+
+    fn check_budget(&self) -> Result<(), IntcodeError<W>> {
+        let out_of_steps = self.budget.steps == Some(0);
+        let out_of_time = self.ticks.is_multiple_of(DEADLINE_INTERVAL)
+            && self.budget.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if out_of_steps || out_of_time {
+            Err(IntcodeError::BudgetExceeded { addr: self.i_ptr })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// `write`, remembering what was overwritten. That is the word in
+    /// memory even under a device, as reading a device may change it.
+    fn write_traced(&mut self, address: usize, value: W) -> Result<MemoryWrite<W>, IntcodeError<W>> {
+        self.check_limit(address)?;
+        let old = self.memory.get(address);
+        self.write(address, value.clone())?;
+        Ok(MemoryWrite { address, old, new: value })
+    }
+}
+
+/// A device and the addresses it answers for.
+#[derive(Clone)]
+struct Mapping<W> {
+    range: Range<usize>,
+    device: Arc<Mutex<dyn Device<W>>>,
+}
+
+impl<W> fmt::Debug for Mapping<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Mapping({:?})", self.range)
+    }
+}
@@ -0,0 +1,69 @@
+//! Running on `i64` words until something doesn't fit, then on wider ones.
+
+use crate::io::SliceInput;
+use crate::{BigInt, Input, IntcodeError, Overflow, Program, StepResult, Word};
+
+/// A machine under `Overflow::Promote`. It starts on `i64` words; when an
+/// instruction overflows, the machine is converted to `i128`, then to
+/// `BigInt`, and the instruction is retried. Nothing is written by an
+/// instruction that overflows, so the run carries on as if it had been on
+/// the wider word all along.
+#[derive(Debug, Clone)]
+pub enum Promoting {
+    I64(Program<i64>),
+    I128(Program<i128>),
+    Big(Program<BigInt>),
+}
+
+impl Promoting {
+    pub fn new(mut program: Program) -> Self {
+        program.overflow = Overflow::Promote;
+        Promoting::I64(program)
+    }
+
+    /// Runs like `Program::execute`. Fails with `IntcodeError::Overflow`
+    /// only if the program can't be converted, because of custom opcodes
+    /// or attached devices.
+    pub fn execute(&mut self, input: &[i64]) -> Result<Vec<BigInt>, IntcodeError<BigInt>> {
+        let mut input = SliceInput::new(input);
+        let mut output = vec![];
+        loop {
+            let promoted = match self {
+                Promoting::I64(program) => match run(program, &mut input, &mut output) {
+                    Err(IntcodeError::Overflow { addr }) => program.convert().map(Promoting::I128).ok_or(IntcodeError::Overflow { addr })?,
+                    result => return result.map(|()| output).map_err(|e| e.map(BigInt::from_i64)),
+                },
+                Promoting::I128(program) => match run(program, &mut input, &mut output) {
+                    Err(IntcodeError::Overflow { addr }) => program.convert().map(Promoting::Big).ok_or(IntcodeError::Overflow { addr })?,
+                    result => return result.map(|()| output).map_err(|e| e.map(|word| big(&word))),
+                },
+                Promoting::Big(program) => return run(program, &mut input, &mut output).map(|()| output),
+            };
+            *self = promoted;
+        }
+    }
+}
+
+/// Runs until the program halts or runs out of input.
+fn run<W: Word>(program: &mut Program<W>, input: &mut SliceInput<i64>, output: &mut Vec<BigInt>) -> Result<(), IntcodeError<W>> {
+    let mut input = Widen(input);
+    loop {
+        match program.run(&mut input)? {
+            StepResult::Output(value) => output.push(big(&value)),
+            StepResult::NeedsInput | StepResult::Halted => break Ok(()),
+        }
+    }
+}
+
+fn big<W: Word>(word: &W) -> BigInt {
+    word.to_string().parse().expect("every word is an integer")
+}
+
+/// Feeds `i64` input to a machine on another word type.
+struct Widen<'a, 'b>(&'a mut SliceInput<'b, i64>);
+
+impl<'a, 'b, W: Word> Input<W> for Widen<'a, 'b> {
+    fn next_input(&mut self) -> Option<W> {
+        self.0.next_input().map(W::from_i64)
+    }
+}
@@ -0,0 +1,193 @@
+//! Recording a program's I/O, and replaying it to check that a run does the
+//! same thing.
+//!
+//! A `Recording` is a `Hook` noting every input consumed and output
+//! produced, with the step (the number of instructions executed before)
+//! it happened at. Saved, it is a text file with one event per line and
+//! the total number of steps at the end:
+//!
+//! ```text
+//! 0 in 5
+//! 3 out 25
+//! steps 5
+//! ```
+//!
+//! Replaying runs a program from the same starting state on the recorded
+//! inputs, and stops at the first step where it does anything different.
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::hook::{Event, Hook};
+use crate::load::{LoadError, ParseError};
+use crate::{IntcodeError, Program, Step};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Io {
+    Input(i64),
+    Output(i64),
+}
+
+impl fmt::Display for Io {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Io::Input(value) => write!(f, "in {}", value),
+            Io::Output(value) => write!(f, "out {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    pub step: u64,
+    pub io: Io,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    pub entries: Vec<Entry>,
+    /// Instructions executed while recording.
+    pub steps: u64,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded inputs, in order.
+    pub fn inputs(&self) -> VecDeque<i64> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry.io {
+                Io::Input(value) => Some(value),
+                Io::Output(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        for entry in &self.entries {
+            writeln!(writer, "{} {}", entry.step, entry.io)?;
+        }
+        writeln!(writer, "steps {}", self.steps)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read(reader: impl BufRead) -> Result<Self, LoadError> {
+        let mut recording = Recording::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let error = |reason: &str| ParseError { line: i + 1, column: 1, word: line.clone(), reason: reason.to_string() };
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let number = |word: &str| word.parse().map_err(|e: std::num::ParseIntError| error(&e.to_string()));
+            let count = |word: &str| u64::try_from(number(word)?).map_err(|_| error("negative step count"));
+            match words[..] {
+                [] => {},
+                ["steps", steps] => recording.steps = count(steps)?,
+                [step, kind, value] => {
+                    let value = number(value)?;
+                    let io = match kind {
+                        "in" => Io::Input(value),
+                        "out" => Io::Output(value),
+                        _ => return Err(error("expected in or out").into()),
+                    };
+                    recording.entries.push(Entry { step: count(step)?, io });
+                },
+                _ => return Err(error("expected <step> in|out <value> or steps <n>").into()),
+            }
+        }
+        Ok(recording)
+    }
+
+    /// Runs `program`, which should be in the state recording started from,
+    /// on the recorded inputs, for no more than the recorded number of steps.
+    /// Returns the first difference from the recording.
+    pub fn replay(&self, program: &mut Program) -> Result<(), Divergence> {
+        let mut input = self.inputs();
+        let mut replay = Recording::new();
+        let mut expected = self.entries.iter();
+
+        while replay.steps < self.steps {
+            let step = replay.steps;
+            let seen = replay.entries.len();
+            // A halted program would go on running its `HLT` (and counting
+            // steps) for as long as it was asked to.
+            let halted = match program.step_with(&mut input, &mut replay) {
+                Ok(Step::NeedsInput) => break,
+                Ok(Step::Halted) => true,
+                Ok(_) => false,
+                Err(error) => return Err(Divergence::Error { step, error }),
+            };
+            for found in &replay.entries[seen..] {
+                match expected.clone().next() {
+                    Some(entry) if entry == found => {
+                        expected.next();
+                    },
+                    Some(entry) if entry.step == step => return Err(Divergence::Io { step, expected: Some(entry.io), found: Some(found.io) }),
+                    _ => return Err(Divergence::Io { step, expected: None, found: Some(found.io) }),
+                }
+            }
+            if let Some(entry) = expected.clone().next().filter(|entry| entry.step <= step) {
+                return Err(Divergence::Io { step: entry.step, expected: Some(entry.io), found: None });
+            }
+            if halted {
+                break;
+            }
+        }
+
+        match expected.next() {
+            Some(entry) => Err(Divergence::Io { step: replay.steps, expected: Some(entry.io), found: None }),
+            None if replay.steps < self.steps => Err(Divergence::Stopped { step: replay.steps, recorded: self.steps }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Hook for Recording {
+    fn on_step(&mut self, event: &Event) {
+        let step = self.steps;
+        let effects = &event.effects;
+        self.entries.extend(effects.input.map(|value| Entry { step, io: Io::Input(value) }));
+        self.entries.extend(effects.output.map(|value| Entry { step, io: Io::Output(value) }));
+        self.steps += 1;
+    }
+}
+
+/// Where a replay first stopped following its recording.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Divergence {
+    /// Different I/O at `step`; `None` for none at all.
+    Io { step: u64, expected: Option<Io>, found: Option<Io> },
+    /// The program halted or ran out of input after `step` instructions,
+    /// where the recording went on for `recorded`.
+    Stopped { step: u64, recorded: u64 },
+    Error { step: u64, error: IntcodeError },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let io = |io: &Option<Io>| io.map_or("no I/O".to_string(), |io| io.to_string());
+        match self {
+            Divergence::Io { step, expected, found } => write!(f, "step {}: expected {}, found {}", step, io(expected), io(found)),
+            Divergence::Stopped { step, recorded } => write!(f, "step {}: stopped, but the recording ran to step {}", step, recorded),
+            Divergence::Error { step, error } => write!(f, "step {}: {}", step, error),
+        }
+    }
+}
+
+impl Error for Divergence {}
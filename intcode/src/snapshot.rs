@@ -0,0 +1,191 @@
+//! Saving and restoring the complete state of a `Program`.
+//!
+//! The on-disk format is little-endian and varint-compressed:
+//!
+//! ```text
+//! magic     b"ICSN"
+//! version   u8 (1)
+//! flags     u8: bit 0 halted, bit 1 sparse memory
+//! i_ptr, relative_base, len, limit, page count      varints
+//! then per non-zero page: index varint, PAGE_SIZE zigzag varints
+//! ```
+//!
+//! Signed values use zigzag encoding, so the mostly small or zero words of a
+//! typical program take one byte each.
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::memory::Page;
+use crate::{Memory, Program, DEFAULT_LIMIT, PAGE_SIZE};
+
+const MAGIC: &[u8; 4] = b"ICSN";
+const VERSION: u8 = 1;
+
+/// A frozen copy of a machine. Taking one is cheap because memory pages
+/// are shared with the program until either side writes to them.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    memory: Memory,
+    halted: bool,
+    i_ptr: usize,
+    relative_base: i64,
+}
+
+impl Program {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.memory.clone(),
+            halted: self.halted,
+            i_ptr: self.i_ptr,
+            relative_base: self.relative_base,
+        }
+    }
+
+    /// Puts the machine back in the state it was in when `snapshot` was
+    /// taken. The snapshot can be restored again later.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.memory = snapshot.memory.clone();
+        self.halted = snapshot.halted;
+        self.i_ptr = snapshot.i_ptr;
+        self.relative_base = snapshot.relative_base;
+    }
+}
+
+impl From<Snapshot> for Program {
+    fn from(snapshot: Snapshot) -> Self {
+        let mut program = Program::with_memory(Memory::dense(vec![]));
+        program.restore(&snapshot);
+        program
+    }
+}
+
+impl Snapshot {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let pages = self.memory.pages()
+            .into_iter()
+            .filter(|(_, page)| page.iter().any(|&word| word != 0))
+            .collect::<Vec<_>>();
+        let flags = self.halted as u8 | (self.memory.is_sparse() as u8) << 1;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, flags])?;
+        write_varint(&mut writer, self.i_ptr as u64)?;
+        write_varint(&mut writer, zigzag(self.relative_base))?;
+        write_varint(&mut writer, self.memory.len() as u64)?;
+        write_varint(&mut writer, self.memory.limit() as u64)?;
+        write_varint(&mut writer, pages.len() as u64)?;
+        for (index, page) in pages {
+            write_varint(&mut writer, index as u64)?;
+            for &word in page.iter() {
+                write_varint(&mut writer, zigzag(word))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut header = [0; 6];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(invalid("not an Intcode snapshot"));
+        }
+        if header[4] != VERSION {
+            return Err(invalid("unsupported snapshot version"));
+        }
+        let flags = header[5];
+
+        let i_ptr = read_size(&mut reader)?;
+        let relative_base = unzigzag(read_varint(&mut reader)?);
+        let len = read_size(&mut reader)?;
+        let limit = read_size(&mut reader)?;
+        if len > limit {
+            return Err(invalid("memory is longer than its limit"));
+        }
+        // Dense memory allocates every page up to the last, so a file of
+        // any size could ask for any amount. Sparse memory only allocates
+        // the pages the file actually holds.
+        if flags & 2 == 0 && len > DEFAULT_LIMIT {
+            return Err(invalid("dense memory past the default limit"));
+        }
+        // Only pages below `len` can have been written.
+        let slots = len.div_ceil(PAGE_SIZE);
+        let count = read_size(&mut reader)?;
+        if count > slots {
+            return Err(invalid("more pages than the memory has room for"));
+        }
+
+        let mut pages = vec![];
+        for _ in 0..count {
+            let index = read_size(&mut reader)?;
+            if index >= slots {
+                return Err(invalid("page past the end of memory"));
+            }
+            let mut page: Box<Page<i64>> = Box::new([0; PAGE_SIZE]);
+            for word in page.iter_mut() {
+                *word = unzigzag(read_varint(&mut reader)?);
+            }
+            pages.push((index, page));
+        }
+
+        Ok(Self {
+            memory: Memory::from_pages(flags & 2 != 0, len, limit, pages),
+            halted: flags & 1 != 0,
+            i_ptr,
+            relative_base,
+        })
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+fn write_varint(writer: &mut impl Write, mut value: u64) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_size(reader: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(read_varint(reader)?).map_err(|_| invalid("size out of range"))
+}
+
+fn read_varint(reader: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
@@ -0,0 +1,262 @@
+//! The solver behind `Path::solve`: integer bounds propagation over the
+//! linear constraints, then a depth-first search over the inputs, trying
+//! values nearest zero first. Every candidate is checked against all the
+//! constraints, linear or not, so a `Sat` answer is always right; `Unsat`
+//! is only claimed when the search ran out of values rather than budget.
+
+use crate::symbolic::{Expr, Linear, Solution};
+
+/// Values tried per input when its bounds allow more.
+const SEARCH_WIDTH: i128 = 1 << 10;
+
+/// Search nodes visited before giving up.
+const BUDGET: usize = 20_000;
+
+/// Rounds of propagation per search node. Constraints like `x < y, y < x`
+/// would otherwise tighten one step at a time across the whole range.
+const ROUNDS: usize = 64;
+
+/// `constant + Σ coefficient * x_symbol`, widened so bound arithmetic
+/// can't overflow.
+struct Row {
+    constant: i128,
+    terms: Vec<(usize, i128)>,
+}
+
+impl Row {
+    fn new(linear: &Linear) -> Self {
+        Row {
+            constant: linear.constant as i128,
+            terms: linear.terms.iter().map(|(&symbol, &coefficient)| (symbol, coefficient as i128)).collect(),
+        }
+    }
+
+    fn negated(&self) -> Self {
+        Row {
+            constant: -self.constant,
+            terms: self.terms.iter().map(|&(symbol, coefficient)| (symbol, -coefficient)).collect(),
+        }
+    }
+}
+
+enum Atom {
+    /// `row <= 0`.
+    Le(Row),
+    /// `row != 0`.
+    Ne(Row),
+}
+
+/// Finds values for `inputs` inputs such that each goal's expression is
+/// non-zero exactly when its flag is set.
+pub(crate) fn solve(goals: &[(Expr, bool)], inputs: usize) -> Solution {
+    let mut atoms = vec![];
+    for (condition, holds) in goals {
+        atomize(condition, *holds, &mut atoms);
+    }
+
+    let mut search = Search { goals, atoms, budget: BUDGET, incomplete: false };
+    let lo = vec![i64::MIN as i128; inputs];
+    let hi = vec![i64::MAX as i128; inputs];
+    match search.search(lo, hi) {
+        Some(model) => Solution::Sat(model),
+        None if search.incomplete => Solution::Unknown,
+        None => Solution::Unsat,
+    }
+}
+
+/// Adds the linear facts implied by `condition` being non-zero (`holds`)
+/// or zero. Anything non-linear is left to the final check.
+fn atomize(condition: &Expr, holds: bool, atoms: &mut Vec<Atom>) {
+    match condition {
+        Expr::Linear(linear) => {
+            let row = Row::new(linear);
+            if holds {
+                atoms.push(Atom::Ne(row));
+            } else {
+                atoms.push(Atom::Le(row.negated()));
+                atoms.push(Atom::Le(row));
+            }
+        },
+        Expr::Lt(a, b) => {
+            if let Some(difference) = linear_difference(a, b) {
+                let mut row = Row::new(&difference);
+                if holds {
+                    // a - b < 0, i.e. a - b + 1 <= 0.
+                    row.constant += 1;
+                    atoms.push(Atom::Le(row));
+                } else {
+                    atoms.push(Atom::Le(row.negated()));
+                }
+            }
+        },
+        Expr::Eq(a, b) => {
+            // A comparison compared with 0 or 1 is the comparison, or its
+            // negation.
+            match (&**a, b.as_constant(), &**b, a.as_constant()) {
+                (comparison @ Expr::Lt(..), Some(value), ..)
+                | (comparison @ Expr::Eq(..), Some(value), ..)
+                | (_, _, comparison @ Expr::Lt(..), Some(value))
+                | (_, _, comparison @ Expr::Eq(..), Some(value)) => {
+                    match value {
+                        0 | 1 => atomize(comparison, holds == (value == 1), atoms),
+                        // A comparison is never anything else: 1 <= 0.
+                        _ if holds => atoms.push(Atom::Le(Row { constant: 1, terms: vec![] })),
+                        _ => {},
+                    }
+                    return;
+                },
+                _ => {},
+            }
+            if let Some(difference) = linear_difference(a, b) {
+                let row = Row::new(&difference);
+                if holds {
+                    atoms.push(Atom::Le(row.negated()));
+                    atoms.push(Atom::Le(row));
+                } else {
+                    atoms.push(Atom::Ne(row));
+                }
+            }
+        },
+        Expr::Add(..) | Expr::Mul(..) => {},
+    }
+}
+
+fn linear_difference(a: &Expr, b: &Expr) -> Option<Linear> {
+    a.as_linear()?.checked_sub(b.as_linear()?)
+}
+
+struct Search<'a> {
+    goals: &'a [(Expr, bool)],
+    atoms: Vec<Atom>,
+    budget: usize,
+    /// Whether some values went untried, through clipping or the budget.
+    incomplete: bool,
+}
+
+impl<'a> Search<'a> {
+    fn search(&mut self, mut lo: Vec<i128>, mut hi: Vec<i128>) -> Option<Vec<i64>> {
+        if self.budget == 0 {
+            self.incomplete = true;
+            return None;
+        }
+        self.budget -= 1;
+
+        if !self.propagate(&mut lo, &mut hi) {
+            return None;
+        }
+
+        let symbol = match (0..lo.len()).filter(|&i| lo[i] < hi[i]).min_by_key(|&i| hi[i] - lo[i]) {
+            Some(symbol) => symbol,
+            None => {
+                let model = lo.iter().map(|&value| value as i64).collect::<Vec<_>>();
+                let satisfied = self.goals.iter().all(|(condition, holds)| (condition.eval(&model) != 0) == *holds);
+                return if satisfied { Some(model) } else { None };
+            },
+        };
+
+        if hi[symbol] - lo[symbol] >= SEARCH_WIDTH {
+            self.incomplete = true;
+        }
+        for value in nearest_zero(lo[symbol], hi[symbol]) {
+            let (mut lo, mut hi) = (lo.clone(), hi.clone());
+            lo[symbol] = value;
+            hi[symbol] = value;
+            if let Some(model) = self.search(lo, hi) {
+                return Some(model);
+            }
+            if self.budget == 0 {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Tightens the bounds using every atom, returning `false` once some
+    /// input has no values left.
+    fn propagate(&self, lo: &mut [i128], hi: &mut [i128]) -> bool {
+        for _ in 0..ROUNDS {
+            let mut changed = false;
+
+            for atom in &self.atoms {
+                match atom {
+                    Atom::Le(row) => {
+                        // Σ a_i x_i <= -constant, so each a_j x_j is at most
+                        // -constant minus the least the other terms can be.
+                        let least = row.terms.iter()
+                            .map(|&(symbol, coefficient)| {
+                                coefficient.saturating_mul(lo[symbol]).min(coefficient.saturating_mul(hi[symbol]))
+                            })
+                            .collect::<Vec<_>>();
+                        let total = least.iter().copied().fold(row.constant, i128::saturating_add);
+                        if total > 0 {
+                            return false;
+                        }
+                        for (&(symbol, coefficient), &least) in row.terms.iter().zip(&least) {
+                            let bound = total.saturating_sub(least).saturating_neg();
+                            if coefficient > 0 {
+                                let bound = bound.div_euclid(coefficient);
+                                if bound < hi[symbol] {
+                                    hi[symbol] = bound;
+                                    changed = true;
+                                }
+                            } else {
+                                let bound = -bound.div_euclid(-coefficient);
+                                if bound > lo[symbol] {
+                                    lo[symbol] = bound;
+                                    changed = true;
+                                }
+                            }
+                        }
+                    },
+                    Atom::Ne(row) => {
+                        let mut free = row.terms.iter().filter(|&&(symbol, _)| lo[symbol] < hi[symbol]);
+                        let (free, more) = (free.next(), free.next());
+                        if more.is_some() {
+                            continue;
+                        }
+                        let fixed = row.terms.iter()
+                            .filter(|&&(symbol, _)| lo[symbol] == hi[symbol])
+                            .map(|&(symbol, coefficient)| coefficient.saturating_mul(lo[symbol]))
+                            .fold(row.constant, i128::saturating_add);
+                        match free {
+                            None if fixed == 0 => return false,
+                            // The one value of x this rules out, if it is an
+                            // integer, can be cut off an end.
+                            Some(&(symbol, coefficient)) if fixed % coefficient == 0 => {
+                                let excluded = -fixed / coefficient;
+                                if excluded == lo[symbol] {
+                                    lo[symbol] += 1;
+                                    changed = true;
+                                } else if excluded == hi[symbol] {
+                                    hi[symbol] -= 1;
+                                    changed = true;
+                                }
+                            },
+                            _ => {},
+                        }
+                    },
+                }
+            }
+
+            if (0..lo.len()).any(|i| lo[i] > hi[i]) {
+                return false;
+            }
+            if !changed {
+                break;
+            }
+        }
+        true
+    }
+}
+
+/// Up to `SEARCH_WIDTH` values of `lo..=hi`, nearest zero first.
+fn nearest_zero(lo: i128, hi: i128) -> impl Iterator<Item = i128> {
+    let start = 0.max(lo).min(hi);
+    (0..).flat_map(move |distance: i128| {
+        let below = if distance == 0 { None } else { Some(start - distance) };
+        Some(start + distance).into_iter().chain(below)
+    })
+    .take_while(move |&value| (value - start).abs() <= hi - lo)
+    .filter(move |&value| lo <= value && value <= hi)
+    .take(SEARCH_WIDTH as usize)
+}
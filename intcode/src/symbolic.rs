@@ -0,0 +1,519 @@
+//! Symbolic execution of Intcode programs.
+//!
+//! Every `In` reads a fresh symbol, `x0`, `x1` and so on, and arithmetic on
+//! symbols builds expressions instead of numbers. A `JNZ`/`JZ` whose
+//! condition depends on a symbol forks the run in two, each side
+//! remembering the condition it assumed. Each run that ends is a `Path`:
+//! the constraints it assumed, what it output, and how it ended.
+//!
+//! Paths can then be asked for an input that makes them output particular
+//! values. The solver handles linear integer constraints itself, by bounds
+//! propagation and a search outwards from zero, and can only check, not
+//! reason about, anything non-linear.
+//!
+//! ```
+//! # use intcode::symbolic::{Explorer, Solution};
+//! // Day 05's example: outputs 999, 1000 or 1001 as the input is below,
+//! // equal to or above 8.
+//! let image = vec![
+//!     3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+//!     1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20,
+//!     1105, 1, 46, 98, 99,
+//! ];
+//! let paths = Explorer::default().explore(&image);
+//! assert_eq!(paths.len(), 3);
+//! let inputs = paths.iter()
+//!     .filter_map(|path| match path.solve_outputs(&[1001]) {
+//!         Solution::Sat(inputs) => Some(inputs),
+//!         _ => None,
+//!     })
+//!     .collect::<Vec<_>>();
+//! assert_eq!(inputs, vec![vec![9]]);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::solver;
+use crate::{decode_instruction, Instruction, IntcodeError, Parameter, ParameterMode, Program};
+
+/// `constant + Σ coefficient * x_symbol`, with no zero coefficients.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Linear {
+    pub constant: i64,
+    pub terms: BTreeMap<usize, i64>,
+}
+
+impl Linear {
+    fn checked_add(&self, other: &Linear) -> Option<Linear> {
+        let mut sum = self.clone();
+        sum.constant = sum.constant.checked_add(other.constant)?;
+        for (&symbol, &coefficient) in &other.terms {
+            let term = sum.terms.entry(symbol).or_insert(0);
+            *term = term.checked_add(coefficient)?;
+            if *term == 0 {
+                sum.terms.remove(&symbol);
+            }
+        }
+        Some(sum)
+    }
+
+    fn checked_scale(&self, factor: i64) -> Option<Linear> {
+        if factor == 0 {
+            return Some(Linear::default());
+        }
+        let mut terms = BTreeMap::new();
+        for (&symbol, &coefficient) in &self.terms {
+            terms.insert(symbol, coefficient.checked_mul(factor)?);
+        }
+        Some(Linear { constant: self.constant.checked_mul(factor)?, terms })
+    }
+
+    /// `self - other`.
+    pub(crate) fn checked_sub(&self, other: &Linear) -> Option<Linear> {
+        self.checked_add(&other.checked_scale(-1)?)
+    }
+}
+
+impl fmt::Display for Linear {
+    /// Renders e.g. `3*x0 - x1 + 5`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        for (&symbol, &coefficient) in &self.terms {
+            let sign = if coefficient < 0 { "-" } else { "+" };
+            match (first, coefficient < 0) {
+                (true, false) => {},
+                (true, true) => write!(f, "-")?,
+                (false, _) => write!(f, " {} ", sign)?,
+            }
+            match coefficient.unsigned_abs() {
+                1 => write!(f, "x{}", symbol)?,
+                magnitude => write!(f, "{}*x{}", magnitude, symbol)?,
+            }
+            first = false;
+        }
+        match (first, self.constant) {
+            (true, constant) => write!(f, "{}", constant),
+            (false, 0) => Ok(()),
+            (false, constant) if constant < 0 => write!(f, " - {}", constant.unsigned_abs()),
+            (false, constant) => write!(f, " + {}", constant),
+        }
+    }
+}
+
+/// A value computed from the inputs. Linear values are kept in normal form;
+/// anything else is a tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Linear(Linear),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    /// `1` if the left side is less than the right, else `0`.
+    Lt(Rc<Expr>, Rc<Expr>),
+    /// `1` if both sides are equal, else `0`.
+    Eq(Rc<Expr>, Rc<Expr>),
+}
+
+impl Expr {
+    pub fn constant(value: i64) -> Self {
+        Expr::Linear(Linear { constant: value, terms: BTreeMap::new() })
+    }
+
+    /// The value of the `symbol`th input.
+    pub fn input(symbol: usize) -> Self {
+        let mut terms = BTreeMap::new();
+        terms.insert(symbol, 1);
+        Expr::Linear(Linear { constant: 0, terms })
+    }
+
+    pub fn as_constant(&self) -> Option<i64> {
+        match self {
+            Expr::Linear(linear) if linear.terms.is_empty() => Some(linear.constant),
+            _ => None,
+        }
+    }
+
+    pub fn as_linear(&self) -> Option<&Linear> {
+        match self {
+            Expr::Linear(linear) => Some(linear),
+            _ => None,
+        }
+    }
+
+    pub fn add(a: &Expr, b: &Expr) -> Self {
+        if let (Some(a), Some(b)) = (a.as_linear(), b.as_linear()) {
+            if let Some(sum) = a.checked_add(b) {
+                return Expr::Linear(sum);
+            }
+        }
+        Expr::Add(Rc::new(a.clone()), Rc::new(b.clone()))
+    }
+
+    pub fn mul(a: &Expr, b: &Expr) -> Self {
+        let scaled = match (a.as_constant(), b.as_constant()) {
+            (Some(factor), _) => b.as_linear().and_then(|b| b.checked_scale(factor)),
+            (_, Some(factor)) => a.as_linear().and_then(|a| a.checked_scale(factor)),
+            _ => None,
+        };
+        match scaled {
+            Some(product) => Expr::Linear(product),
+            None => Expr::Mul(Rc::new(a.clone()), Rc::new(b.clone())),
+        }
+    }
+
+    pub fn lt(a: &Expr, b: &Expr) -> Self {
+        match Expr::difference(a, b) {
+            Some(difference) => Expr::constant((difference < 0) as i64),
+            None => Expr::Lt(Rc::new(a.clone()), Rc::new(b.clone())),
+        }
+    }
+
+    pub fn eq(a: &Expr, b: &Expr) -> Self {
+        match Expr::difference(a, b) {
+            Some(difference) => Expr::constant((difference == 0) as i64),
+            None => Expr::Eq(Rc::new(a.clone()), Rc::new(b.clone())),
+        }
+    }
+
+    /// `a - b`, if it doesn't depend on the inputs.
+    fn difference(a: &Expr, b: &Expr) -> Option<i64> {
+        a.as_linear()?.checked_sub(b.as_linear()?)
+            .filter(|difference| difference.terms.is_empty())
+            .map(|difference| difference.constant)
+    }
+
+    /// Evaluates the expression with the `i`th input set to `inputs[i]`,
+    /// wrapping on overflow. Missing inputs count as zero.
+    pub fn eval(&self, inputs: &[i64]) -> i64 {
+        match self {
+            Expr::Linear(linear) => linear.terms.iter().fold(linear.constant, |sum, (&symbol, &coefficient)| {
+                let value = inputs.get(symbol).copied().unwrap_or(0);
+                sum.wrapping_add(coefficient.wrapping_mul(value))
+            }),
+            Expr::Add(a, b) => a.eval(inputs).wrapping_add(b.eval(inputs)),
+            Expr::Mul(a, b) => a.eval(inputs).wrapping_mul(b.eval(inputs)),
+            Expr::Lt(a, b) => (a.eval(inputs) < b.eval(inputs)) as i64,
+            Expr::Eq(a, b) => (a.eval(inputs) == b.eval(inputs)) as i64,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Linear(linear) => write!(f, "{}", linear),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mul(a, b) => write!(f, "({} * {})", a, b),
+            Expr::Lt(a, b) => write!(f, "({} < {})", a, b),
+            Expr::Eq(a, b) => write!(f, "({} == {})", a, b),
+        }
+    }
+}
+
+/// A branch condition assumed by a path: `condition` is non-zero if
+/// `holds`, zero otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    /// The address of the branch that assumed it.
+    pub addr: usize,
+    pub condition: Expr,
+    pub holds: bool,
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.condition, self.holds) {
+            (Expr::Lt(..), true) | (Expr::Eq(..), true) => write!(f, "{}", self.condition),
+            (Expr::Lt(..), false) | (Expr::Eq(..), false) => write!(f, "!{}", self.condition),
+            (condition, true) => write!(f, "{} != 0", condition),
+            (condition, false) => write!(f, "{} == 0", condition),
+        }
+    }
+}
+
+/// How a path ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum End {
+    Halted,
+    Error(IntcodeError),
+    /// The path ran for `max_steps` instructions.
+    StepLimit,
+    /// The path wanted more than `max_inputs` inputs.
+    InputLimit,
+    /// Execution depended on a symbol in a way that can't be followed: an
+    /// opcode, address, jump target or relative base that is one.
+    Unsupported { addr: usize, reason: &'static str },
+}
+
+impl fmt::Display for End {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            End::Halted => write!(f, "halted"),
+            End::Error(e) => write!(f, "error: {}", e),
+            End::StepLimit => write!(f, "step limit"),
+            End::InputLimit => write!(f, "input limit"),
+            End::Unsupported { addr, reason } => write!(f, "{} at {}", reason, addr),
+        }
+    }
+}
+
+/// The answer to a set of constraints: values for the inputs that satisfy
+/// them, proof that none do, or neither.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    Sat(Vec<i64>),
+    Unsat,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    /// Every symbolic branch condition taken, in order.
+    pub constraints: Vec<Constraint>,
+    pub outputs: Vec<Expr>,
+    /// How many inputs the path read.
+    pub inputs: usize,
+    pub end: End,
+}
+
+impl Path {
+    /// Finds inputs that lead down this path.
+    pub fn solve(&self) -> Solution {
+        self.solve_outputs(&[])
+    }
+
+    /// Finds inputs that lead down this path and make its first outputs
+    /// `expected`.
+    pub fn solve_outputs(&self, expected: &[i64]) -> Solution {
+        if expected.len() > self.outputs.len() {
+            return Solution::Unsat;
+        }
+        let mut goals = self.goals();
+        for (output, &value) in self.outputs.iter().zip(expected) {
+            goals.push((Expr::eq(output, &Expr::constant(value)), true));
+        }
+        solver::solve(&goals, self.inputs)
+    }
+
+    fn goals(&self) -> Vec<(Expr, bool)> {
+        self.constraints.iter().map(|constraint| (constraint.condition.clone(), constraint.holds)).collect()
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} after {} input(s)", self.end, self.inputs)?;
+        for constraint in &self.constraints {
+            writeln!(f, "  if {}", constraint)?;
+        }
+        for output in &self.outputs {
+            writeln!(f, "  out {}", output)?;
+        }
+        Ok(())
+    }
+}
+
+/// Explores every path through a program, within limits.
+#[derive(Debug, Clone)]
+pub struct Explorer {
+    /// Instructions executed per path before giving up on it.
+    pub max_steps: usize,
+    /// Paths reported before giving up on the rest.
+    pub max_paths: usize,
+    /// Inputs read per path before giving up on it.
+    pub max_inputs: usize,
+}
+
+impl Default for Explorer {
+    fn default() -> Self {
+        Self { max_steps: 100_000, max_paths: 256, max_inputs: 16 }
+    }
+}
+
+/// One path being explored. Memory is concrete except for the addresses
+/// in `symbols`.
+#[derive(Clone)]
+struct State {
+    program: Program,
+    symbols: HashMap<usize, Expr>,
+    constraints: Vec<Constraint>,
+    outputs: Vec<Expr>,
+    inputs: usize,
+    steps: usize,
+}
+
+enum Flow {
+    Continue,
+    Forked(Vec<State>),
+    End(End),
+}
+
+impl Explorer {
+    /// Explores `image` from address 0, depth first. Branches that the
+    /// solver can prove impossible are not followed.
+    pub fn explore(&self, image: &[i64]) -> Vec<Path> {
+        let mut pending = vec![State {
+            program: Program::init(image.to_vec()),
+            symbols: HashMap::new(),
+            constraints: vec![],
+            outputs: vec![],
+            inputs: 0,
+            steps: 0,
+        }];
+        let mut paths = vec![];
+
+        while let Some(mut state) = pending.pop() {
+            if paths.len() >= self.max_paths {
+                break;
+            }
+            let end = loop {
+                match self.step(&mut state) {
+                    Ok(Flow::Continue) => {},
+                    Ok(Flow::Forked(states)) => {
+                        pending.extend(states);
+                        break None;
+                    },
+                    Ok(Flow::End(end)) => break Some(end),
+                    Err(e) => break Some(End::Error(e)),
+                }
+            };
+            if let Some(end) = end {
+                paths.push(Path { constraints: state.constraints, outputs: state.outputs, inputs: state.inputs, end });
+            }
+        }
+
+        paths
+    }
+
+    fn step(&self, state: &mut State) -> Result<Flow, IntcodeError> {
+        if state.steps >= self.max_steps {
+            return Ok(Flow::End(End::StepLimit));
+        }
+        state.steps += 1;
+
+        let i_ptr = state.program.i_ptr;
+        if state.symbols.contains_key(&i_ptr) {
+            return Ok(Flow::End(End::Unsupported { addr: i_ptr, reason: "symbolic code" }));
+        }
+        // A symbol in an immediate parameter is just a symbolic operand, as
+        // `operand` sees; one that is an address can't be followed.
+        let instruction = decode_instruction(&state.program.memory, i_ptr)?;
+        for (i, parameter) in instruction.parameters().into_iter().enumerate() {
+            if parameter.mode != ParameterMode::Immediate && state.symbols.contains_key(&(i_ptr + 1 + i)) {
+                return Ok(Flow::End(End::Unsupported { addr: i_ptr, reason: "symbolic address" }));
+            }
+        }
+
+        use Instruction::*;
+        match &instruction {
+            Halt => {
+                state.program.halted = true;
+                return Ok(Flow::End(End::Halted));
+            },
+            Add(p1, p2, p3) | Mul(p1, p2, p3) | Lt(p1, p2, p3) | Eq(p1, p2, p3) => {
+                let store = p3.get_value_write(&state.program)?;
+                let (a, b) = (state.operand(i_ptr + 1, p1)?, state.operand(i_ptr + 2, p2)?);
+                let value = match instruction {
+                    Add(..) => Expr::add(&a, &b),
+                    Mul(..) => Expr::mul(&a, &b),
+                    Lt(..) => Expr::lt(&a, &b),
+                    _ => Expr::eq(&a, &b),
+                };
+                state.write(store, value)?;
+            },
+            In(p1) => {
+                let store = p1.get_value_write(&state.program)?;
+                state.program.read(store)?;
+                if state.inputs >= self.max_inputs {
+                    return Ok(Flow::End(End::InputLimit));
+                }
+                state.write(store, Expr::input(state.inputs))?;
+                state.inputs += 1;
+            },
+            Out(p1) => {
+                let value = state.operand(i_ptr + 1, p1)?;
+                state.outputs.push(value);
+            },
+            Jnz(p1, p2) | Jz(p1, p2) => {
+                let (condition, target) = (state.operand(i_ptr + 1, p1)?, state.operand(i_ptr + 2, p2)?);
+                let jump_if_nonzero = matches!(instruction, Jnz(..));
+                let target = match target.as_constant() {
+                    Some(target) => target,
+                    None => return Ok(Flow::End(End::Unsupported { addr: i_ptr, reason: "symbolic jump target" })),
+                };
+
+                match condition.as_constant() {
+                    Some(value) => {
+                        if (value != 0) == jump_if_nonzero {
+                            state.program.i_ptr = state.program.address(target)?;
+                            return Ok(Flow::Continue);
+                        }
+                    },
+                    None => {
+                        let mut forks = vec![];
+                        for &jump in &[false, true] {
+                            let mut fork = state.clone();
+                            fork.constraints.push(Constraint {
+                                addr: i_ptr,
+                                condition: condition.clone(),
+                                holds: jump == jump_if_nonzero,
+                            });
+                            let goals = fork.constraints.iter()
+                                .map(|constraint| (constraint.condition.clone(), constraint.holds))
+                                .collect::<Vec<_>>();
+                            if solver::solve(&goals, fork.inputs) == Solution::Unsat {
+                                continue;
+                            }
+                            if jump {
+                                fork.program.i_ptr = fork.program.address(target)?;
+                            } else {
+                                fork.program.i_ptr += instruction.size();
+                            }
+                            forks.push(fork);
+                        }
+                        return Ok(Flow::Forked(forks));
+                    },
+                }
+            },
+            ChangeBase(p1) => {
+                match state.operand(i_ptr + 1, p1)?.as_constant() {
+                    Some(value) => state.program.relative_base = state.program.add(state.program.relative_base, value)?,
+                    None => return Ok(Flow::End(End::Unsupported { addr: i_ptr, reason: "symbolic relative base" })),
+                }
+            },
+            Custom { .. } => return Ok(Flow::End(End::Unsupported { addr: i_ptr, reason: "custom instruction" })),
+        }
+
+        state.program.i_ptr += instruction.size();
+        Ok(Flow::Continue)
+    }
+}
+
+impl State {
+    /// The value of `parameter`, whose word is at `slot`.
+    fn operand(&self, slot: usize, parameter: &Parameter) -> Result<Expr, IntcodeError> {
+        if parameter.mode == ParameterMode::Immediate {
+            return Ok(self.symbols.get(&slot).cloned().unwrap_or_else(|| Expr::constant(parameter.value)));
+        }
+        let address = parameter.get_value_write(&self.program)?;
+        match self.symbols.get(&address) {
+            Some(value) => Ok(value.clone()),
+            None => Ok(Expr::constant(self.program.read(address)?)),
+        }
+    }
+
+    fn write(&mut self, address: usize, value: Expr) -> Result<(), IntcodeError> {
+        match value.as_constant() {
+            Some(value) => {
+                self.program.write(address, value)?;
+                self.symbols.remove(&address);
+            },
+            None => {
+                self.program.read(address)?;
+                self.symbols.insert(address, value);
+            },
+        }
+        Ok(())
+    }
+}
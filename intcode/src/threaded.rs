@@ -0,0 +1,34 @@
+//! Running machines on their own OS threads, talking over `mpsc` channels.
+
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::{Input, IntcodeError, Output, Program};
+
+/// `In` blocks until a value arrives, or gives up once every sender is gone.
+impl Input for Receiver<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+/// Values sent after the receiver is gone are dropped.
+impl Output for Sender<i64> {
+    fn output(&mut self, value: i64) {
+        let _ = self.send(value);
+    }
+}
+
+/// Runs `program` on a new thread until it halts, or until it needs input
+/// and every sender of `input` has hung up. The program is handed back in
+/// whichever state it stopped.
+pub fn spawn(
+    mut program: Program,
+    mut input: Receiver<i64>,
+    mut output: Sender<i64>
+) -> JoinHandle<Result<Program, IntcodeError>> {
+    thread::spawn(move || {
+        program.run_io(&mut input, &mut output)?;
+        Ok(program)
+    })
+}
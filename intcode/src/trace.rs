@@ -0,0 +1,143 @@
+//! Trace sinks that record every executed instruction, one line per step, so
+//! that traces from two VM versions can be diffed.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::hook::{Event, Hook};
+
+/// Writes a human readable trace, e.g.
+///
+/// ```text
+/// 0 0002 rb=0 EQ @21, #8, @20 ops=[8,8,20] write[20]=1(was 0)
+/// ```
+pub struct TextTrace<W: Write> {
+    writer: W,
+    steps: u64,
+    error: Option<io::Error>,
+}
+
+/// Writes one JSON object per step, e.g.
+///
+/// ```text
+/// {"step":0,"i_ptr":2,"relative_base":0,"op":"EQ","operands":[8,8,20],"write":{"address":20,"old":0,"new":1},"jump":null,"input":null,"output":null,"new_base":null}
+/// ```
+pub struct JsonTrace<W: Write> {
+    writer: W,
+    steps: u64,
+    error: Option<io::Error>,
+}
+
+impl TextTrace<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> TextTrace<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, steps: 0, error: None }
+    }
+
+    /// Flushes the trace, returning the first write error if there was one.
+    pub fn finish(mut self) -> io::Result<W> {
+        finish(&mut self.writer, self.error.take())?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Hook for TextTrace<W> {
+    fn on_step(&mut self, event: &Event) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let effects = &event.effects;
+        let mut line = format!(
+            "{} {:04} rb={} {} ops=[{}]",
+            self.steps, event.i_ptr, event.relative_base, event.instruction, list(event.operands()),
+        );
+        if let Some(write) = effects.write {
+            line += &format!(" write[{}]={}(was {})", write.address, write.new, write.old);
+        }
+        if let Some(target) = effects.jump {
+            line += &format!(" jump={}", target);
+        }
+        if let Some(val) = effects.input {
+            line += &format!(" in={}", val);
+        }
+        if let Some(val) = effects.output {
+            line += &format!(" out={}", val);
+        }
+        if let Some(base) = effects.relative_base {
+            line += &format!(" rb:={}", base);
+        }
+
+        self.steps += 1;
+        self.error = writeln!(self.writer, "{}", line).err();
+    }
+}
+
+impl JsonTrace<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> JsonTrace<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, steps: 0, error: None }
+    }
+
+    /// Flushes the trace, returning the first write error if there was one.
+    pub fn finish(mut self) -> io::Result<W> {
+        finish(&mut self.writer, self.error.take())?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Hook for JsonTrace<W> {
+    fn on_step(&mut self, event: &Event) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let effects = &event.effects;
+        let write = match effects.write {
+            Some(write) => format!(r#"{{"address":{},"old":{},"new":{}}}"#, write.address, write.old, write.new),
+            None => "null".to_string(),
+        };
+        let line = format!(
+            r#"{{"step":{},"i_ptr":{},"relative_base":{},"op":"{}","operands":[{}],"write":{},"jump":{},"input":{},"output":{},"new_base":{}}}"#,
+            self.steps,
+            event.i_ptr,
+            event.relative_base,
+            event.instruction.mnemonic(),
+            list(event.operands()),
+            write,
+            json(effects.jump),
+            json(effects.input),
+            json(effects.output),
+            json(effects.relative_base),
+        );
+
+        self.steps += 1;
+        self.error = writeln!(self.writer, "{}", line).err();
+    }
+}
+
+fn finish(writer: &mut impl Write, error: Option<io::Error>) -> io::Result<()> {
+    match error {
+        Some(e) => Err(e),
+        None => writer.flush(),
+    }
+}
+
+fn list(values: &[i64]) -> String {
+    values.iter().map(i64::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn json<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "null".to_string(), |v| v.to_string())
+}
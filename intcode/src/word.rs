@@ -0,0 +1,63 @@
+//! The numbers an Intcode machine computes with.
+
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// A memory word. Implemented for `i32` (what days 05 and 07 were written
+/// for), `i64`, `i128` and `BigInt`.
+pub trait Word: Clone + Default + PartialEq + PartialOrd + Hash + fmt::Debug + fmt::Display + FromStr + 'static {
+    fn from_i64(value: i64) -> Self;
+
+    /// The value as an `i64`, if it fits.
+    fn to_i64(&self) -> Option<i64>;
+
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    fn wrapping_add(&self, other: &Self) -> Self;
+
+    fn wrapping_mul(&self, other: &Self) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < Self::default()
+    }
+}
+
+macro_rules! primitive_word {
+    ($($t:ty),*) => {$(
+        impl Word for $t {
+            fn from_i64(value: i64) -> Self {
+                value as $t
+            }
+
+            fn to_i64(&self) -> Option<i64> {
+                use std::convert::TryFrom;
+                i64::try_from(*self).ok()
+            }
+
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *other)
+            }
+
+            fn wrapping_add(&self, other: &Self) -> Self {
+                <$t>::wrapping_add(*self, *other)
+            }
+
+            fn wrapping_mul(&self, other: &Self) -> Self {
+                <$t>::wrapping_mul(*self, *other)
+            }
+        }
+    )*};
+}
+
+primitive_word!(i32, i64, i128);
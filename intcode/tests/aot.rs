@@ -0,0 +1,65 @@
+//! Translated programs against the interpreter.
+//!
+//! The modules in `aot/` are checked in as `intcode aot` wrote them; the
+//! first test fails if the translator's output has since changed, and they
+//! should be regenerated with
+//! `cargo run -- aot --name <name> <program> > tests/aot/<name>.rs`.
+
+use std::collections::VecDeque;
+
+use intcode::{aot, Program, StepResult};
+
+include!("aot/day05.rs");
+include!("aot/day09.rs");
+include!("aot/selfmod.rs");
+
+const DAY05: &str = include_str!("../../05/src/input");
+const DAY09: &str = include_str!("../../09/src/input");
+/// Turns its own `ADD` at 4 into a `MUL` before running it.
+const SELFMOD: &str = include_str!("aot/selfmod");
+
+type Run = fn(&mut Program, &mut VecDeque<i64>, &mut Vec<i64>) -> Result<StepResult, intcode::IntcodeError>;
+
+/// Runs `text` translated and interpreted on `input`, and checks that both
+/// output and leave memory the same.
+fn cross_check(text: &str, run: Run, input: &[i64]) {
+    let image = intcode::parse::<i64>(text).unwrap();
+
+    let mut translated = Program::init(image.clone());
+    let mut output = vec![];
+    assert_eq!(run(&mut translated, &mut input.iter().copied().collect(), &mut output), Ok(StepResult::Halted));
+
+    let mut interpreted = Program::init(image);
+    assert_eq!(interpreted.execute(input), Ok(output));
+    let len = interpreted.memory.len();
+    assert_eq!(translated.memory.len(), len);
+    assert_eq!(translated.memory.range(0, len), interpreted.memory.range(0, len));
+}
+
+#[test]
+fn checked_in_modules_are_up_to_date() {
+    for &(text, name, source) in &[
+        (DAY05, "day05", include_str!("aot/day05.rs")),
+        (DAY09, "day09", include_str!("aot/day09.rs")),
+        (SELFMOD, "selfmod", include_str!("aot/selfmod.rs")),
+    ] {
+        assert!(aot::translate(&intcode::parse::<i64>(text).unwrap(), name) == source, "tests/aot/{}.rs is stale", name);
+    }
+}
+
+#[test]
+fn day05_matches_the_interpreter() {
+    cross_check(DAY05, day05::run, &[1]);
+    cross_check(DAY05, day05::run, &[5]);
+}
+
+#[test]
+fn day09_matches_the_interpreter() {
+    cross_check(DAY09, day09::run, &[1]);
+    cross_check(DAY09, day09::run, &[2]);
+}
+
+#[test]
+fn self_modifying_code_matches_the_interpreter() {
+    cross_check(SELFMOD, selfmod::run, &[]);
+}
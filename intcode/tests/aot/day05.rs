@@ -0,0 +1,47 @@
+/// Translated from a 678-word Intcode image by `intcode::aot`.
+#[allow(clippy::all, unreachable_code, unreachable_patterns, unused_mut, unused_parens)]
+pub mod day05 {
+    use intcode::aot::Code;
+    use intcode::{Input, IntcodeError, Output, Program, StepResult};
+
+    pub const CODE: Code = Code(&[
+        (0, &[3, 225, 1, 225, 6, 6]),
+    ]);
+
+    pub fn run(
+        program: &mut Program,
+        input: &mut impl Input,
+        output: &mut impl Output,
+    ) -> Result<StepResult, IntcodeError> {
+        if !CODE.matches(&program.memory) || program.is_limited() {
+            return program.run_io(input, output);
+        }
+
+        let mut ip = program.i_ptr;
+        loop {
+            match ip {
+                0 => {
+                    // 0000: IN @225
+                    program.i_ptr = 0;
+                    let store = 225;
+                    program.check_limit(store)?;
+                    let value = match input.next_input() {
+                        Some(value) => value,
+                        None => return Ok(StepResult::NeedsInput),
+                    };
+                    program.write(store, value)?;
+                    // 0002: ADD @225, @6, @6
+                    program.i_ptr = 2;
+                    let store = 6;
+                    let value = program.add(program.read(225)?, program.read(6)?)?;
+                    program.write(store, value)?;
+                    ip = 6;
+                },
+                _ => {
+                    program.i_ptr = ip;
+                    return program.run_io(input, output);
+                },
+            }
+        }
+    }
+}
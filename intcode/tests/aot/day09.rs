@@ -0,0 +1,725 @@
+/// Translated from a 973-word Intcode image by `intcode::aot`.
+#[allow(clippy::all, unreachable_code, unreachable_patterns, unused_mut, unused_parens)]
+pub mod day09 {
+    use intcode::aot::Code;
+    use intcode::{Input, IntcodeError, Output, Program, StepResult};
+
+    pub const CODE: Code = Code(&[
+        (0, &[1102, 34463338, 34463338, 63, 1007, 63, 34463338, 63, 1005, 63, 53, 1101, 3, 0, 1000, 109, 988, 209, 12, 9, 1000, 209, 6, 209, 3, 203, 0, 1008, 1000, 1, 63, 1005, 63, 65, 1008, 1000, 2, 63, 1005, 63, 904, 1008, 1000, 0, 63, 1005, 63, 58, 4, 25, 104, 0, 99, 4, 0, 104, 0, 99, 4, 17, 104, 0, 99]),
+        (65, &[1102, 1, 31, 1018, 1102, 352, 1, 1023, 1101, 0, 1, 1021, 1101, 0, 33, 1003, 1102, 1, 36, 1007, 1102, 21, 1, 1005, 1101, 359, 0, 1022, 1101, 0, 787, 1024, 1102, 1, 24, 1011, 1101, 30, 0, 1014, 1101, 22, 0, 1016, 1101, 0, 0, 1020, 1102, 1, 29, 1000, 1101, 778, 0, 1025, 1102, 23, 1, 1017, 1102, 1, 28, 1002, 1101, 38, 0, 1019, 1102, 1, 27, 1013, 1102, 1, 32, 1012, 1101, 0, 37, 1006, 1101, 444, 0, 1027, 1102, 1, 20, 1009, 1101, 0, 447, 1026, 1101, 0, 39, 1008, 1101, 35, 0, 1010, 1102, 559, 1, 1028, 1102, 26, 1, 1004, 1102, 1, 25, 1015, 1102, 1, 34, 1001, 1101, 0, 554, 1029, 109, -3, 2101, 0, 9, 63, 1008, 63, 34, 63, 1005, 63, 205, 1001, 64, 1, 64, 1105, 1, 207, 4, 187, 1002, 64, 2, 64, 109, 23, 21107, 40, 39, -7, 1005, 1013, 227, 1001, 64, 1, 64, 1106, 0, 229, 4, 213, 1002, 64, 2, 64, 109, -17, 1202, -2, 1, 63, 1008, 63, 36, 63, 1005, 63, 249, 1106, 0, 255, 4, 235, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -6, 1202, 10, 1, 63, 1008, 63, 36, 63, 1005, 63, 277, 4, 261, 1106, 0, 281, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -2, 1208, 9, 26, 63, 1005, 63, 303, 4, 287, 1001, 64, 1, 64, 1106, 0, 303, 1002, 64, 2, 64, 109, 32, 1206, -7, 321, 4, 309, 1001, 64, 1, 64, 1106, 0, 321, 1002, 64, 2, 64, 109, -29, 1207, 7, 20, 63, 1005, 63, 337, 1105, 1, 343, 4, 327, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 27, 2105, 1, -2]),
+        (904, &[21102, 27, 1, 1, 21101, 0, 915, 0, 1106, 0, 922]),
+        (922, &[109, 3, 1207, -2, 3, 63, 1005, 63, 964, 21201, -2, -1, 1, 21102, 1, 942, 0, 1106, 0, 922]),
+        (964, &[21201, -2, 0, -2, 109, -3, 2106, 0, 0]),
+    ]);
+
+    pub fn run(
+        program: &mut Program,
+        input: &mut impl Input,
+        output: &mut impl Output,
+    ) -> Result<StepResult, IntcodeError> {
+        if !CODE.matches(&program.memory) || program.is_limited() {
+            return program.run_io(input, output);
+        }
+
+        let mut ip = program.i_ptr;
+        loop {
+            match ip {
+                0 => {
+                    // 0000: MUL #34463338, #34463338, @63
+                    program.i_ptr = 0;
+                    let store = 63;
+                    let value = program.mul((34463338), (34463338))?;
+                    program.write(store, value)?;
+                    // 0004: LT @63, #34463338, @63
+                    program.i_ptr = 4;
+                    let store = 63;
+                    let value = (program.read(63)? < (34463338)) as i64;
+                    program.write(store, value)?;
+                    // 0008: JNZ @63, #53
+                    program.i_ptr = 8;
+                    let (test, target) = (program.read(63)?, (53));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 11;
+                },
+                11 => {
+                    // 0011: ADD #3, #0, @1000
+                    program.i_ptr = 11;
+                    let store = 1000;
+                    let value = program.add((3), (0))?;
+                    program.write(store, value)?;
+                    // 0015: ARB #988
+                    program.i_ptr = 15;
+                    program.relative_base = program.add(program.relative_base, (988))?;
+                    // 0017: ARB [rb+12]
+                    program.i_ptr = 17;
+                    program.relative_base = program.add(program.relative_base, program.read(program.address(program.add(program.relative_base, 12)?)?)?)?;
+                    // 0019: ARB @1000
+                    program.i_ptr = 19;
+                    program.relative_base = program.add(program.relative_base, program.read(1000)?)?;
+                    // 0021: ARB [rb+6]
+                    program.i_ptr = 21;
+                    program.relative_base = program.add(program.relative_base, program.read(program.address(program.add(program.relative_base, 6)?)?)?)?;
+                    // 0023: ARB [rb+3]
+                    program.i_ptr = 23;
+                    program.relative_base = program.add(program.relative_base, program.read(program.address(program.add(program.relative_base, 3)?)?)?)?;
+                    ip = 25;
+                },
+                25 => {
+                    // 0025: IN [rb+0]
+                    program.i_ptr = 25;
+                    let store = program.address(program.add(program.relative_base, 0)?)?;
+                    program.check_limit(store)?;
+                    let value = match input.next_input() {
+                        Some(value) => value,
+                        None => return Ok(StepResult::NeedsInput),
+                    };
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 27; return program.run_io(input, output); }
+                    // 0027: EQ @1000, #1, @63
+                    program.i_ptr = 27;
+                    let store = 63;
+                    let value = (program.read(1000)? == (1)) as i64;
+                    program.write(store, value)?;
+                    // 0031: JNZ @63, #65
+                    program.i_ptr = 31;
+                    let (test, target) = (program.read(63)?, (65));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 34;
+                },
+                34 => {
+                    // 0034: EQ @1000, #2, @63
+                    program.i_ptr = 34;
+                    let store = 63;
+                    let value = (program.read(1000)? == (2)) as i64;
+                    program.write(store, value)?;
+                    // 0038: JNZ @63, #904
+                    program.i_ptr = 38;
+                    let (test, target) = (program.read(63)?, (904));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 41;
+                },
+                41 => {
+                    // 0041: EQ @1000, #0, @63
+                    program.i_ptr = 41;
+                    let store = 63;
+                    let value = (program.read(1000)? == (0)) as i64;
+                    program.write(store, value)?;
+                    // 0045: JNZ @63, #58
+                    program.i_ptr = 45;
+                    let (test, target) = (program.read(63)?, (58));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 48;
+                },
+                48 => {
+                    // 0048: OUT @25
+                    program.i_ptr = 48;
+                    output.output(program.read(25)?);
+                    // 0050: OUT #0
+                    program.i_ptr = 50;
+                    output.output((0));
+                    // 0052: HLT
+                    program.i_ptr = 52;
+                    program.halted = true;
+                    return Ok(StepResult::Halted);
+                },
+                53 => {
+                    // 0053: OUT @0
+                    program.i_ptr = 53;
+                    output.output(program.read(0)?);
+                    // 0055: OUT #0
+                    program.i_ptr = 55;
+                    output.output((0));
+                    // 0057: HLT
+                    program.i_ptr = 57;
+                    program.halted = true;
+                    return Ok(StepResult::Halted);
+                },
+                58 => {
+                    // 0058: OUT @17
+                    program.i_ptr = 58;
+                    output.output(program.read(17)?);
+                    // 0060: OUT #0
+                    program.i_ptr = 60;
+                    output.output((0));
+                    // 0062: HLT
+                    program.i_ptr = 62;
+                    program.halted = true;
+                    return Ok(StepResult::Halted);
+                },
+                65 => {
+                    // 0065: MUL #1, #31, @1018
+                    program.i_ptr = 65;
+                    let store = 1018;
+                    let value = program.mul((1), (31))?;
+                    program.write(store, value)?;
+                    // 0069: MUL #352, #1, @1023
+                    program.i_ptr = 69;
+                    let store = 1023;
+                    let value = program.mul((352), (1))?;
+                    program.write(store, value)?;
+                    // 0073: ADD #0, #1, @1021
+                    program.i_ptr = 73;
+                    let store = 1021;
+                    let value = program.add((0), (1))?;
+                    program.write(store, value)?;
+                    // 0077: ADD #0, #33, @1003
+                    program.i_ptr = 77;
+                    let store = 1003;
+                    let value = program.add((0), (33))?;
+                    program.write(store, value)?;
+                    // 0081: MUL #1, #36, @1007
+                    program.i_ptr = 81;
+                    let store = 1007;
+                    let value = program.mul((1), (36))?;
+                    program.write(store, value)?;
+                    // 0085: MUL #21, #1, @1005
+                    program.i_ptr = 85;
+                    let store = 1005;
+                    let value = program.mul((21), (1))?;
+                    program.write(store, value)?;
+                    // 0089: ADD #359, #0, @1022
+                    program.i_ptr = 89;
+                    let store = 1022;
+                    let value = program.add((359), (0))?;
+                    program.write(store, value)?;
+                    // 0093: ADD #0, #787, @1024
+                    program.i_ptr = 93;
+                    let store = 1024;
+                    let value = program.add((0), (787))?;
+                    program.write(store, value)?;
+                    // 0097: MUL #1, #24, @1011
+                    program.i_ptr = 97;
+                    let store = 1011;
+                    let value = program.mul((1), (24))?;
+                    program.write(store, value)?;
+                    // 0101: ADD #30, #0, @1014
+                    program.i_ptr = 101;
+                    let store = 1014;
+                    let value = program.add((30), (0))?;
+                    program.write(store, value)?;
+                    // 0105: ADD #22, #0, @1016
+                    program.i_ptr = 105;
+                    let store = 1016;
+                    let value = program.add((22), (0))?;
+                    program.write(store, value)?;
+                    // 0109: ADD #0, #0, @1020
+                    program.i_ptr = 109;
+                    let store = 1020;
+                    let value = program.add((0), (0))?;
+                    program.write(store, value)?;
+                    // 0113: MUL #1, #29, @1000
+                    program.i_ptr = 113;
+                    let store = 1000;
+                    let value = program.mul((1), (29))?;
+                    program.write(store, value)?;
+                    // 0117: ADD #778, #0, @1025
+                    program.i_ptr = 117;
+                    let store = 1025;
+                    let value = program.add((778), (0))?;
+                    program.write(store, value)?;
+                    // 0121: MUL #23, #1, @1017
+                    program.i_ptr = 121;
+                    let store = 1017;
+                    let value = program.mul((23), (1))?;
+                    program.write(store, value)?;
+                    // 0125: MUL #1, #28, @1002
+                    program.i_ptr = 125;
+                    let store = 1002;
+                    let value = program.mul((1), (28))?;
+                    program.write(store, value)?;
+                    // 0129: ADD #38, #0, @1019
+                    program.i_ptr = 129;
+                    let store = 1019;
+                    let value = program.add((38), (0))?;
+                    program.write(store, value)?;
+                    // 0133: MUL #1, #27, @1013
+                    program.i_ptr = 133;
+                    let store = 1013;
+                    let value = program.mul((1), (27))?;
+                    program.write(store, value)?;
+                    // 0137: MUL #1, #32, @1012
+                    program.i_ptr = 137;
+                    let store = 1012;
+                    let value = program.mul((1), (32))?;
+                    program.write(store, value)?;
+                    // 0141: ADD #0, #37, @1006
+                    program.i_ptr = 141;
+                    let store = 1006;
+                    let value = program.add((0), (37))?;
+                    program.write(store, value)?;
+                    // 0145: ADD #444, #0, @1027
+                    program.i_ptr = 145;
+                    let store = 1027;
+                    let value = program.add((444), (0))?;
+                    program.write(store, value)?;
+                    // 0149: MUL #1, #20, @1009
+                    program.i_ptr = 149;
+                    let store = 1009;
+                    let value = program.mul((1), (20))?;
+                    program.write(store, value)?;
+                    // 0153: ADD #0, #447, @1026
+                    program.i_ptr = 153;
+                    let store = 1026;
+                    let value = program.add((0), (447))?;
+                    program.write(store, value)?;
+                    // 0157: ADD #0, #39, @1008
+                    program.i_ptr = 157;
+                    let store = 1008;
+                    let value = program.add((0), (39))?;
+                    program.write(store, value)?;
+                    // 0161: ADD #35, #0, @1010
+                    program.i_ptr = 161;
+                    let store = 1010;
+                    let value = program.add((35), (0))?;
+                    program.write(store, value)?;
+                    // 0165: MUL #559, #1, @1028
+                    program.i_ptr = 165;
+                    let store = 1028;
+                    let value = program.mul((559), (1))?;
+                    program.write(store, value)?;
+                    // 0169: MUL #26, #1, @1004
+                    program.i_ptr = 169;
+                    let store = 1004;
+                    let value = program.mul((26), (1))?;
+                    program.write(store, value)?;
+                    // 0173: MUL #1, #25, @1015
+                    program.i_ptr = 173;
+                    let store = 1015;
+                    let value = program.mul((1), (25))?;
+                    program.write(store, value)?;
+                    // 0177: MUL #1, #34, @1001
+                    program.i_ptr = 177;
+                    let store = 1001;
+                    let value = program.mul((1), (34))?;
+                    program.write(store, value)?;
+                    // 0181: ADD #0, #554, @1029
+                    program.i_ptr = 181;
+                    let store = 1029;
+                    let value = program.add((0), (554))?;
+                    program.write(store, value)?;
+                    // 0185: ARB #-3
+                    program.i_ptr = 185;
+                    program.relative_base = program.add(program.relative_base, (-3))?;
+                    // 0187: ADD #0, [rb+9], @63
+                    program.i_ptr = 187;
+                    let store = 63;
+                    let value = program.add((0), program.read(program.address(program.add(program.relative_base, 9)?)?)?)?;
+                    program.write(store, value)?;
+                    // 0191: EQ @63, #34, @63
+                    program.i_ptr = 191;
+                    let store = 63;
+                    let value = (program.read(63)? == (34)) as i64;
+                    program.write(store, value)?;
+                    // 0195: JNZ @63, #205
+                    program.i_ptr = 195;
+                    let (test, target) = (program.read(63)?, (205));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 198;
+                },
+                198 => {
+                    // 0198: ADD @64, #1, @64
+                    program.i_ptr = 198;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    // 0202: JNZ #1, #207
+                    program.i_ptr = 202;
+                    let (test, target) = ((1), (207));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 205;
+                },
+                205 => {
+                    // 0205: OUT @187
+                    program.i_ptr = 205;
+                    output.output(program.read(187)?);
+                    ip = 207;
+                },
+                207 => {
+                    // 0207: MUL @64, #2, @64
+                    program.i_ptr = 207;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0211: ARB #23
+                    program.i_ptr = 211;
+                    program.relative_base = program.add(program.relative_base, (23))?;
+                    // 0213: LT #40, #39, [rb-7]
+                    program.i_ptr = 213;
+                    let store = program.address(program.add(program.relative_base, -7)?)?;
+                    let value = ((40) < (39)) as i64;
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 217; return program.run_io(input, output); }
+                    // 0217: JNZ @1013, #227
+                    program.i_ptr = 217;
+                    let (test, target) = (program.read(1013)?, (227));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 220;
+                },
+                220 => {
+                    // 0220: ADD @64, #1, @64
+                    program.i_ptr = 220;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    // 0224: JZ #0, #229
+                    program.i_ptr = 224;
+                    let (test, target) = ((0), (229));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 227;
+                },
+                227 => {
+                    // 0227: OUT @213
+                    program.i_ptr = 227;
+                    output.output(program.read(213)?);
+                    ip = 229;
+                },
+                229 => {
+                    // 0229: MUL @64, #2, @64
+                    program.i_ptr = 229;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0233: ARB #-17
+                    program.i_ptr = 233;
+                    program.relative_base = program.add(program.relative_base, (-17))?;
+                    // 0235: MUL [rb-2], #1, @63
+                    program.i_ptr = 235;
+                    let store = 63;
+                    let value = program.mul(program.read(program.address(program.add(program.relative_base, -2)?)?)?, (1))?;
+                    program.write(store, value)?;
+                    // 0239: EQ @63, #36, @63
+                    program.i_ptr = 239;
+                    let store = 63;
+                    let value = (program.read(63)? == (36)) as i64;
+                    program.write(store, value)?;
+                    // 0243: JNZ @63, #249
+                    program.i_ptr = 243;
+                    let (test, target) = (program.read(63)?, (249));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 246;
+                },
+                246 => {
+                    // 0246: JZ #0, #255
+                    program.i_ptr = 246;
+                    let (test, target) = ((0), (255));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 249;
+                },
+                249 => {
+                    // 0249: OUT @235
+                    program.i_ptr = 249;
+                    output.output(program.read(235)?);
+                    // 0251: ADD @64, #1, @64
+                    program.i_ptr = 251;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    ip = 255;
+                },
+                255 => {
+                    // 0255: MUL @64, #2, @64
+                    program.i_ptr = 255;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0259: ARB #-6
+                    program.i_ptr = 259;
+                    program.relative_base = program.add(program.relative_base, (-6))?;
+                    // 0261: MUL [rb+10], #1, @63
+                    program.i_ptr = 261;
+                    let store = 63;
+                    let value = program.mul(program.read(program.address(program.add(program.relative_base, 10)?)?)?, (1))?;
+                    program.write(store, value)?;
+                    // 0265: EQ @63, #36, @63
+                    program.i_ptr = 265;
+                    let store = 63;
+                    let value = (program.read(63)? == (36)) as i64;
+                    program.write(store, value)?;
+                    // 0269: JNZ @63, #277
+                    program.i_ptr = 269;
+                    let (test, target) = (program.read(63)?, (277));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 272;
+                },
+                272 => {
+                    // 0272: OUT @261
+                    program.i_ptr = 272;
+                    output.output(program.read(261)?);
+                    // 0274: JZ #0, #281
+                    program.i_ptr = 274;
+                    let (test, target) = ((0), (281));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 277;
+                },
+                277 => {
+                    // 0277: ADD @64, #1, @64
+                    program.i_ptr = 277;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    ip = 281;
+                },
+                281 => {
+                    // 0281: MUL @64, #2, @64
+                    program.i_ptr = 281;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0285: ARB #-2
+                    program.i_ptr = 285;
+                    program.relative_base = program.add(program.relative_base, (-2))?;
+                    // 0287: EQ [rb+9], #26, @63
+                    program.i_ptr = 287;
+                    let store = 63;
+                    let value = (program.read(program.address(program.add(program.relative_base, 9)?)?)? == (26)) as i64;
+                    program.write(store, value)?;
+                    // 0291: JNZ @63, #303
+                    program.i_ptr = 291;
+                    let (test, target) = (program.read(63)?, (303));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 294;
+                },
+                294 => {
+                    // 0294: OUT @287
+                    program.i_ptr = 294;
+                    output.output(program.read(287)?);
+                    // 0296: ADD @64, #1, @64
+                    program.i_ptr = 296;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    // 0300: JZ #0, #303
+                    program.i_ptr = 300;
+                    let (test, target) = ((0), (303));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 303;
+                },
+                303 => {
+                    // 0303: MUL @64, #2, @64
+                    program.i_ptr = 303;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0307: ARB #32
+                    program.i_ptr = 307;
+                    program.relative_base = program.add(program.relative_base, (32))?;
+                    // 0309: JZ [rb-7], #321
+                    program.i_ptr = 309;
+                    let (test, target) = (program.read(program.address(program.add(program.relative_base, -7)?)?)?, (321));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 312;
+                },
+                312 => {
+                    // 0312: OUT @309
+                    program.i_ptr = 312;
+                    output.output(program.read(309)?);
+                    // 0314: ADD @64, #1, @64
+                    program.i_ptr = 314;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    // 0318: JZ #0, #321
+                    program.i_ptr = 318;
+                    let (test, target) = ((0), (321));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 321;
+                },
+                321 => {
+                    // 0321: MUL @64, #2, @64
+                    program.i_ptr = 321;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0325: ARB #-29
+                    program.i_ptr = 325;
+                    program.relative_base = program.add(program.relative_base, (-29))?;
+                    // 0327: LT [rb+7], #20, @63
+                    program.i_ptr = 327;
+                    let store = 63;
+                    let value = (program.read(program.address(program.add(program.relative_base, 7)?)?)? < (20)) as i64;
+                    program.write(store, value)?;
+                    // 0331: JNZ @63, #337
+                    program.i_ptr = 331;
+                    let (test, target) = (program.read(63)?, (337));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 334;
+                },
+                334 => {
+                    // 0334: JNZ #1, #343
+                    program.i_ptr = 334;
+                    let (test, target) = ((1), (343));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 337;
+                },
+                337 => {
+                    // 0337: OUT @327
+                    program.i_ptr = 337;
+                    output.output(program.read(327)?);
+                    // 0339: ADD @64, #1, @64
+                    program.i_ptr = 339;
+                    let store = 64;
+                    let value = program.add(program.read(64)?, (1))?;
+                    program.write(store, value)?;
+                    ip = 343;
+                },
+                343 => {
+                    // 0343: MUL @64, #2, @64
+                    program.i_ptr = 343;
+                    let store = 64;
+                    let value = program.mul(program.read(64)?, (2))?;
+                    program.write(store, value)?;
+                    // 0347: ARB #27
+                    program.i_ptr = 347;
+                    program.relative_base = program.add(program.relative_base, (27))?;
+                    // 0349: JNZ #1, [rb-2]
+                    program.i_ptr = 349;
+                    let (test, target) = ((1), program.read(program.address(program.add(program.relative_base, -2)?)?)?);
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 352;
+                },
+                904 => {
+                    // 0904: MUL #27, #1, [rb+1]
+                    program.i_ptr = 904;
+                    let store = program.address(program.add(program.relative_base, 1)?)?;
+                    let value = program.mul((27), (1))?;
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 908; return program.run_io(input, output); }
+                    // 0908: ADD #0, #915, [rb+0]
+                    program.i_ptr = 908;
+                    let store = program.address(program.add(program.relative_base, 0)?)?;
+                    let value = program.add((0), (915))?;
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 912; return program.run_io(input, output); }
+                    // 0912: JZ #0, #922
+                    program.i_ptr = 912;
+                    let (test, target) = ((0), (922));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 915;
+                },
+                922 => {
+                    // 0922: ARB #3
+                    program.i_ptr = 922;
+                    program.relative_base = program.add(program.relative_base, (3))?;
+                    // 0924: LT [rb-2], #3, @63
+                    program.i_ptr = 924;
+                    let store = 63;
+                    let value = (program.read(program.address(program.add(program.relative_base, -2)?)?)? < (3)) as i64;
+                    program.write(store, value)?;
+                    // 0928: JNZ @63, #964
+                    program.i_ptr = 928;
+                    let (test, target) = (program.read(63)?, (964));
+                    if test != 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 931;
+                },
+                931 => {
+                    // 0931: ADD [rb-2], #-1, [rb+1]
+                    program.i_ptr = 931;
+                    let store = program.address(program.add(program.relative_base, 1)?)?;
+                    let value = program.add(program.read(program.address(program.add(program.relative_base, -2)?)?)?, (-1))?;
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 935; return program.run_io(input, output); }
+                    // 0935: MUL #1, #942, [rb+0]
+                    program.i_ptr = 935;
+                    let store = program.address(program.add(program.relative_base, 0)?)?;
+                    let value = program.mul((1), (942))?;
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 939; return program.run_io(input, output); }
+                    // 0939: JZ #0, #922
+                    program.i_ptr = 939;
+                    let (test, target) = ((0), (922));
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 942;
+                },
+                964 => {
+                    // 0964: ADD [rb-2], #0, [rb-2]
+                    program.i_ptr = 964;
+                    let store = program.address(program.add(program.relative_base, -2)?)?;
+                    let value = program.add(program.read(program.address(program.add(program.relative_base, -2)?)?)?, (0))?;
+                    program.write(store, value)?;
+                    if CODE.contains(store) { program.i_ptr = 968; return program.run_io(input, output); }
+                    // 0968: ARB #-3
+                    program.i_ptr = 968;
+                    program.relative_base = program.add(program.relative_base, (-3))?;
+                    // 0970: JZ #0, [rb+0]
+                    program.i_ptr = 970;
+                    let (test, target) = ((0), program.read(program.address(program.add(program.relative_base, 0)?)?)?);
+                    if test == 0 {
+                        ip = program.address(target)?;
+                        continue;
+                    }
+                    ip = 973;
+                },
+                _ => {
+                    program.i_ptr = ip;
+                    return program.run_io(input, output);
+                },
+            }
+        }
+    }
+}
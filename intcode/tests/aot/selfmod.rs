@@ -0,0 +1,51 @@
+/// Translated from a 11-word Intcode image by `intcode::aot`.
+#[allow(clippy::all, unreachable_code, unreachable_patterns, unused_mut, unused_parens)]
+pub mod selfmod {
+    use intcode::aot::Code;
+    use intcode::{Input, IntcodeError, Output, Program, StepResult};
+
+    pub const CODE: Code = Code(&[
+        (0, &[1101, 1, 1, 4, 1, 0, 0, 0, 4, 0, 99]),
+    ]);
+
+    pub fn run(
+        program: &mut Program,
+        input: &mut impl Input,
+        output: &mut impl Output,
+    ) -> Result<StepResult, IntcodeError> {
+        if !CODE.matches(&program.memory) || program.is_limited() {
+            return program.run_io(input, output);
+        }
+
+        let mut ip = program.i_ptr;
+        loop {
+            match ip {
+                0 => {
+                    // 0000: ADD #1, #1, @4
+                    program.i_ptr = 0;
+                    let store = 4;
+                    let value = program.add((1), (1))?;
+                    program.write(store, value)?;
+                    { program.i_ptr = 4; return program.run_io(input, output); }
+                    // 0004: ADD @0, @0, @0
+                    program.i_ptr = 4;
+                    let store = 0;
+                    let value = program.add(program.read(0)?, program.read(0)?)?;
+                    program.write(store, value)?;
+                    { program.i_ptr = 8; return program.run_io(input, output); }
+                    // 0008: OUT @0
+                    program.i_ptr = 8;
+                    output.output(program.read(0)?);
+                    // 0010: HLT
+                    program.i_ptr = 10;
+                    program.halted = true;
+                    return Ok(StepResult::Halted);
+                },
+                _ => {
+                    program.i_ptr = ip;
+                    return program.run_io(input, output);
+                },
+            }
+        }
+    }
+}
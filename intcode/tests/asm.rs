@@ -0,0 +1,18 @@
+//! Assembler errors and where they are reported.
+
+use intcode::asm;
+
+#[test]
+fn errors_have_positions() {
+    let cases = [
+        ("hlt\nx: .data x+9223372036854775807", 2, 10, "address x+9223372036854775807 out of range"),
+        ("jnz #1, #nowhere", 1, 10, "undefined label nowhere"),
+        ("frob @1", 1, 1, "unknown mnemonic frob"),
+        ("add #1, #2, #3", 1, 13, "cannot write to an immediate operand"),
+        ("x: hlt\nx: hlt", 2, 1, "duplicate label x"),
+    ];
+    for &(source, line, column, message) in &cases {
+        let error = asm::assemble(source).unwrap_err();
+        assert_eq!((error.line, error.column, error.message.as_str()), (line, column, message), "{:?}", source);
+    }
+}
@@ -0,0 +1,77 @@
+//! Step budgets, deadlines and the loop detector.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use intcode::{IntcodeError, Program};
+
+#[test]
+fn budget_exceeded_resumes() {
+    // Counts down from 10, outputting each value.
+    let image = intcode::parse::<i64>("1001,13,-1,13,4,13,1005,13,0,99,0,0,0,10").unwrap();
+    let mut whole = Program::init(image.clone());
+    let expected = whole.execute(&[]).unwrap();
+
+    let mut program = Program::init(image);
+    let mut output = vec![];
+    let mut stops = 0;
+    loop {
+        program.budget.steps = Some(7);
+        match program.run_io(&mut VecDeque::new(), &mut output) {
+            Ok(_) => break,
+            Err(IntcodeError::BudgetExceeded { .. }) => stops += 1,
+            Err(e) => panic!("{}", e),
+        }
+    }
+    assert_eq!(output, expected);
+    assert!(stops > 1);
+}
+
+#[test]
+fn halting_costs_nothing() {
+    let image = intcode::parse::<i64>("1101,0,0,5,99,0").unwrap();
+    let mut program = Program::init(image);
+    program.budget.steps = Some(1);
+    assert_eq!(program.execute(&[]), Ok(vec![]));
+    assert!(program.halted);
+    assert_eq!(program.budget.steps, Some(0));
+    assert_eq!(program.execute(&[]), Ok(vec![]));
+    assert_eq!(program.budget.steps, Some(0));
+}
+
+#[test]
+fn deadline_stops_a_spinning_program() {
+    let mut program = Program::init(intcode::parse::<i64>("1001,7,1,7,1105,1,0,0").unwrap());
+    program.budget.deadline = Some(Instant::now() + Duration::from_millis(50));
+    assert_eq!(program.execute(&[]), Err(IntcodeError::BudgetExceeded { addr: program.i_ptr }));
+}
+
+#[test]
+fn loops_without_io_are_detected() {
+    for &text in &["1105,1,0", "1101,0,0,9,1105,1,0,0,0,0", "109,1,109,-1,1105,1,0"] {
+        let mut program = Program::init(intcode::parse::<i64>(text).unwrap());
+        program.set_loop_detection(true);
+        assert!(matches!(program.execute(&[]), Err(IntcodeError::InfiniteLoop { .. })), "{}", text);
+    }
+}
+
+#[test]
+fn progress_is_not_a_loop() {
+    // A counter never repeats a state; a program waiting on input isn't
+    // looping; and outputs break up what would otherwise be a loop.
+    let mut counter = Program::init(intcode::parse::<i64>("1001,7,1,7,1105,1,0,0").unwrap());
+    counter.set_loop_detection(true);
+    counter.budget.steps = Some(100_000);
+    assert!(matches!(counter.execute(&[]), Err(IntcodeError::BudgetExceeded { .. })));
+
+    let mut echo = Program::init(intcode::parse::<i64>("3,9,4,9,1105,1,0,0,0,0").unwrap());
+    echo.set_loop_detection(true);
+    for value in 0..5 {
+        assert_eq!(echo.execute(&[7]), Ok(vec![7]), "round {}", value);
+    }
+
+    let mut ticker = Program::init(intcode::parse::<i64>("104,1,1105,1,0").unwrap());
+    ticker.set_loop_detection(true);
+    ticker.budget.steps = Some(1_000);
+    assert!(matches!(ticker.execute(&[]), Err(IntcodeError::BudgetExceeded { .. })));
+}
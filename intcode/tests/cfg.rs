@@ -0,0 +1,66 @@
+//! Control-flow graphs of a small program with every kind of block and region.
+
+use intcode::asm;
+use intcode::cfg::{Cfg, Edge, Region, RegionKind};
+
+/// Halts on a zero input and otherwise jumps through `target`, past a
+/// block nothing reaches.
+const SOURCE: &str = "
+        in @x
+        jz @x, #done
+        jnz #1, @target
+        out #1
+        hlt
+done:   hlt
+x:      .data 0
+target: .data done
+";
+
+const DOT: &str = r#"digraph intcode {
+    node [shape=box, fontname="monospace"];
+    b0 [label="0000: IN @12\l0002: JZ @12, #11\l"];
+    b5 [label="0005: JNZ #1, @13\l"];
+    b11 [label="0011: HLT\l"];
+    b0 -> b11 [label="jump"];
+    b0 -> b5;
+    b5 -> computed [style=dashed];
+    computed [label="computed jump", shape=diamond];
+    r8 [label="0008-0010: dead code (3 words)", style=dashed];
+    r12 [label="0012-0013: data (2 words)", shape=note];
+}
+"#;
+
+fn cfg() -> Cfg {
+    Cfg::build(&asm::assemble(SOURCE).unwrap())
+}
+
+#[test]
+fn blocks_end_at_jumps_and_targets() {
+    let cfg = cfg();
+    let bounds = cfg.blocks.values()
+        .map(|block| (block.start, block.end, block.instructions.len(), block.edges.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(bounds, [
+        (0, 5, 2, vec![Edge::Jump(11), Edge::Next(5)]),
+        (5, 8, 1, vec![Edge::Computed]),
+        (11, 12, 1, vec![]),
+    ]);
+    assert_eq!(cfg.block(11).map(|block| block.instructions[0].0), Some(11));
+    assert!(cfg.block(8).is_none());
+}
+
+#[test]
+fn unreached_words_are_dead_code_or_data() {
+    let region = |start, end, kind| Region { start, end, kind };
+    assert_eq!(cfg().regions, [
+        region(0, 8, RegionKind::Code),
+        region(8, 11, RegionKind::Dead),
+        region(11, 12, RegionKind::Code),
+        region(12, 14, RegionKind::Data),
+    ]);
+}
+
+#[test]
+fn dot_snapshot() {
+    assert_eq!(cfg().to_dot(), DOT);
+}
@@ -0,0 +1,211 @@
+//! The example programs from the puzzle text, run on every word type, plus
+//! round-trip properties of the decoder, disassembler and assembler.
+
+use std::fmt;
+
+use intcode::{asm, decode_instruction, disasm, BigInt, Instruction, Memory, Parameter, ParameterMode, Program, Word};
+
+/// Runs `text` on `W` words, returning the output and the final memory.
+fn run<W: Word>(text: &str, input: &[i64]) -> (Vec<i64>, Vec<i64>)
+where
+    W::Err: fmt::Display,
+{
+    let image = intcode::parse::<W>(text).unwrap();
+    let len = image.len();
+    let input = input.iter().map(|&value| W::from_i64(value)).collect::<Vec<_>>();
+    let mut program = Program::init(image);
+    let output = program.execute(&input).unwrap();
+    assert!(program.halted);
+    let words = |words: Vec<W>| words.iter().map(|word| word.to_i64().unwrap()).collect();
+    (words(output), words(program.memory.range(0, len)))
+}
+
+/// `run` on every word type, checking they all agree.
+fn run_all(text: &str, input: &[i64]) -> (Vec<i64>, Vec<i64>) {
+    let result = run::<i64>(text, input);
+    assert_eq!(run::<i32>(text, input), result);
+    assert_eq!(run::<i128>(text, input), result);
+    assert_eq!(run::<BigInt>(text, input), result);
+    result
+}
+
+#[test]
+fn day02_examples() {
+    let examples = [
+        ("1,9,10,3,2,3,11,0,99,30,40,50", "3500,9,10,70,2,3,11,0,99,30,40,50"),
+        ("1,0,0,0,99", "2,0,0,0,99"),
+        ("2,3,0,3,99", "2,3,0,6,99"),
+        ("2,4,4,5,99,0", "2,4,4,5,99,9801"),
+        ("1,1,1,4,99,5,6,0,99", "30,1,1,4,2,5,6,0,99"),
+    ];
+    for &(program, memory) in &examples {
+        assert_eq!(run_all(program, &[]).1, intcode::parse::<i64>(memory).unwrap(), "{}", program);
+    }
+}
+
+#[test]
+fn day05_io_and_modes() {
+    assert_eq!(run_all("3,0,4,0,99", &[42]).0, [42]);
+    assert_eq!(run_all("1002,4,3,4,33", &[]).1, [1002, 4, 3, 4, 99]);
+    assert_eq!(run_all("1101,100,-1,4,0", &[]).1, [1101, 100, -1, 4, 99]);
+}
+
+#[test]
+fn day05_compare_to_8() {
+    // Each program and what it outputs for 7, 8 and 9.
+    let examples = [
+        ("3,9,8,9,10,9,4,9,99,-1,8", [0, 1, 0]),
+        ("3,9,7,9,10,9,4,9,99,-1,8", [1, 0, 0]),
+        ("3,3,1108,-1,8,3,4,3,99", [0, 1, 0]),
+        ("3,3,1107,-1,8,3,4,3,99", [1, 0, 0]),
+        (include_str!("../../05/src/example"), [999, 1000, 1001]),
+    ];
+    for &(program, expected) in &examples {
+        for (input, &expected) in (7..=9).zip(&expected) {
+            assert_eq!(run_all(program, &[input]).0, [expected], "{} with input {}", program, input);
+        }
+    }
+}
+
+#[test]
+fn day05_jumps() {
+    for &program in &["3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", "3,3,1105,-1,9,1101,0,0,12,4,12,99,1"] {
+        assert_eq!(run_all(program, &[0]).0, [0], "{}", program);
+        assert_eq!(run_all(program, &[5]).0, [1], "{}", program);
+    }
+}
+
+#[test]
+fn day09_quine() {
+    let quine = include_str!("../../09/src/example");
+    assert_eq!(run::<i64>(quine, &[]).0, intcode::parse::<i64>(quine).unwrap());
+    assert_eq!(run::<BigInt>(quine, &[]).0, intcode::parse::<i64>(quine).unwrap());
+}
+
+#[test]
+fn day09_large_numbers() {
+    let sixteen_digits = "1102,34915192,34915192,7,4,7,99,0";
+    assert_eq!(run::<i64>(sixteen_digits, &[]).0, [1219070632396864]);
+    assert_eq!(run::<i128>(sixteen_digits, &[]).0, [1219070632396864]);
+    assert_eq!(run::<BigInt>(sixteen_digits, &[]).0, [1219070632396864]);
+    assert!(Program::init(intcode::parse::<i32>(sixteen_digits).unwrap()).execute(&[]).is_err());
+
+    assert_eq!(run::<i64>("104,1125899906842624,99", &[]).0, [1125899906842624]);
+}
+
+#[test]
+fn bigint_words_past_i64() {
+    let image = intcode::parse::<BigInt>("1102,9223372036854775807,9223372036854775807,7,4,7,99,0").unwrap();
+    let output = Program::init(image).execute(&[]).unwrap();
+    assert_eq!(output[0].to_string(), "85070591730234615847396907784232501249");
+}
+
+/// A xorshift generator, so the properties below check the same cases on
+/// every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// Mostly small values, like real programs, with the odd extreme one.
+    fn value(&mut self) -> i64 {
+        match self.below(8) {
+            0 => self.next() as i64,
+            1 => -(self.below(1000) as i64),
+            _ => self.below(1000) as i64,
+        }
+    }
+
+    fn instruction(&mut self) -> Instruction {
+        let opcode = self.below(10);
+        let mut parameter = || {
+            let mode = [ParameterMode::Position, ParameterMode::Immediate, ParameterMode::Relative][self.below(3) as usize];
+            Parameter { mode, value: self.value() }
+        };
+        use Instruction::*;
+        match opcode {
+            0 => Add(parameter(), parameter(), parameter()),
+            1 => Mul(parameter(), parameter(), parameter()),
+            2 => In(parameter()),
+            3 => Out(parameter()),
+            4 => Jnz(parameter(), parameter()),
+            5 => Jz(parameter(), parameter()),
+            6 => Lt(parameter(), parameter(), parameter()),
+            7 => Eq(parameter(), parameter(), parameter()),
+            8 => ChangeBase(parameter()),
+            _ => Halt,
+        }
+    }
+
+    /// Instructions with data mixed in.
+    fn image(&mut self) -> Vec<i64> {
+        let mut image = vec![];
+        for _ in 0..self.below(40) {
+            if self.below(4) == 0 {
+                image.push(self.value());
+            } else {
+                image.extend(self.instruction().encode());
+            }
+        }
+        image
+    }
+}
+
+#[test]
+fn encode_decode_round_trip() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..10_000 {
+        let instruction = rng.instruction();
+        let memory = Memory::dense(instruction.encode());
+        assert_eq!(decode_instruction(&memory, 0), Ok(instruction));
+    }
+}
+
+#[test]
+fn decode_encode_round_trip() {
+    // Arbitrary words that happen to decode re-encode to words that decode
+    // the same, even if the opcode had stray mode digits.
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut decoded = 0;
+    for _ in 0..100_000 {
+        let words = (0..4).map(|_| rng.value()).collect::<Vec<_>>();
+        if let Ok(instruction) = decode_instruction(&Memory::dense(words.clone()), 0) {
+            let encoded = instruction.encode();
+            assert_eq!(encoded[1..], words[1..instruction.size()]);
+            assert_eq!(decode_instruction(&Memory::dense(encoded), 0), Ok(instruction));
+            decoded += 1;
+        }
+    }
+    assert!(decoded > 1000);
+}
+
+fn reassemble(image: &[i64]) -> Vec<i64> {
+    let listing = disasm::disassemble(image).iter().map(ToString::to_string).collect::<Vec<_>>().join("\n");
+    asm::assemble(&listing).unwrap_or_else(|e| panic!("{}\n{}", e, listing))
+}
+
+#[test]
+fn disassembly_reassembles() {
+    let mut rng = Rng(0xdead_beef_cafe_f00d);
+    for _ in 0..1_000 {
+        let image = rng.image();
+        assert_eq!(reassemble(&image), image);
+    }
+}
+
+#[test]
+fn puzzle_inputs_reassemble() {
+    for &text in &[include_str!("../../05/src/input"), include_str!("../../07/src/input"), include_str!("../../09/src/input")] {
+        let image = intcode::parse::<i64>(text).unwrap();
+        assert_eq!(reassemble(&image), image);
+    }
+}
@@ -0,0 +1,105 @@
+//! Running the debugger backwards.
+
+use intcode::debugger::{Debugger, LastWrite, Stop};
+use intcode::Program;
+
+/// Sets the relative base to 20, reads a value to 21, outputs it, adds one
+/// to it in 13 and outputs that.
+const PROGRAM: &str = "109,20,203,1,204,1,22101,1,1,-7,4,13,99,0";
+
+fn debugger(input: &[i64]) -> Debugger {
+    let mut debugger = Debugger::new(Program::init(intcode::parse::<i64>(PROGRAM).unwrap()));
+    debugger.input.extend(input);
+    debugger
+}
+
+#[test]
+fn stepping_back_undoes_everything() {
+    let mut debugger = debugger(&[41]);
+    let start = debugger.program.clone();
+    assert_eq!(debugger.resume(), Stop::Halted);
+    assert_eq!(debugger.output, [41, 42]);
+
+    while debugger.step_back() != Stop::Start {}
+    assert_eq!(debugger.program.memory.range(0, 22), start.memory.range(0, 22));
+    assert_eq!((debugger.program.i_ptr, debugger.program.relative_base, debugger.program.halted), (0, 0, false));
+    assert_eq!(debugger.input, [41]);
+    assert!(debugger.output.is_empty());
+
+    // And forwards again, the same way.
+    assert_eq!(debugger.resume(), Stop::Halted);
+    assert_eq!(debugger.output, [41, 42]);
+}
+
+#[test]
+fn stepping_back_restores_length_and_budget() {
+    // Writes far past the end of the image, then halts.
+    let mut debugger = Debugger::new(Program::init(intcode::parse::<i64>("1101,1,2,5000,99").unwrap()));
+    debugger.program.budget.steps = Some(10);
+    assert_eq!(debugger.resume(), Stop::Halted);
+    assert_eq!(debugger.program.memory.len(), 5001);
+    assert_eq!(debugger.program.budget.steps, Some(9));
+
+    assert_eq!(debugger.step_back(), Stop::Stepped);
+    assert_eq!(debugger.program.budget.steps, Some(9));
+    assert_eq!(debugger.step_back(), Stop::Stepped);
+    assert_eq!(debugger.program.memory.len(), 5);
+    assert_eq!(debugger.program.memory[5000], 0);
+    assert_eq!(debugger.program.budget.steps, Some(10));
+}
+
+#[test]
+fn reverse_continue_finds_the_last_write() {
+    let mut debugger = debugger(&[41]);
+    assert_eq!(debugger.resume(), Stop::Halted);
+
+    let add = intcode::decode_instruction(&debugger.program.memory, 6).ok();
+    assert_eq!(debugger.last_write(13), Some(LastWrite { step: 3, i_ptr: 6, old: 0, new: 42, instruction: add }));
+    assert!(debugger.last_write(14).is_none());
+    assert!(debugger.command("last-write 13").ends_with("by step 3, 0006: ADD #1, [rb+1], [rb-7]"));
+
+    assert_eq!(debugger.reverse(Some(21)), Stop::Watchpoint { address: 21, old: 0, new: 41 });
+    assert_eq!(debugger.program.i_ptr, 2);
+    assert_eq!(debugger.program.relative_base, 20);
+    assert_eq!(debugger.input, [41]);
+}
+
+#[test]
+fn reverse_continue_stops_at_breakpoints_and_watchpoints() {
+    let mut debugger = debugger(&[41]);
+    assert_eq!(debugger.resume(), Stop::Halted);
+    debugger.add_breakpoint(4);
+    debugger.add_watchpoint(13);
+    assert_eq!(debugger.reverse(None), Stop::Watchpoint { address: 13, old: 0, new: 42 });
+    assert_eq!(debugger.reverse(None), Stop::Breakpoint(4));
+    assert_eq!(debugger.reverse(None), Stop::Start);
+    assert_eq!(debugger.program.i_ptr, 0);
+}
+
+#[test]
+fn huge_counts_are_clamped() {
+    let mut debugger = debugger(&[]);
+    let listing = debugger.command("disasm 4611686018427387904");
+    assert!(listing.contains("HLT"), "{}", listing);
+    let dump = debugger.command("mem 0 9223372036854775807");
+    assert_eq!(dump.lines().count(), 14);
+    assert_eq!(debugger.command("mem 9223372036854775807 9223372036854775807").lines().count(), 14);
+}
+
+#[test]
+fn last_write_shows_the_instruction_as_it_ran() {
+    // Writes 99 over the start of its own `ADD` at 0, and halts.
+    let mut debugger = Debugger::new(Program::init(intcode::parse::<i64>("1101,98,1,0,99").unwrap()));
+    assert_eq!(debugger.resume(), Stop::Halted);
+    assert_eq!(debugger.program.memory[0], 99);
+    assert!(debugger.command("last-write 0").ends_with("by step 0, 0000: ADD #98, #1, @0"));
+}
+
+#[test]
+fn negative_counts_are_usage_errors() {
+    let mut debugger = debugger(&[41]);
+    assert_eq!(debugger.command("step -1"), "usage: step [n]");
+    assert_eq!(debugger.command("step-back -1"), "usage: step-back [n]");
+    assert!(debugger.command("step x").starts_with("bad argument"));
+    assert_eq!(debugger.program.i_ptr, 0);
+}
@@ -0,0 +1,76 @@
+//! Memory-mapped devices.
+
+use intcode::device::{Device, Framebuffer, Random};
+use intcode::Program;
+
+/// Counts how often each of its words was read and keeps what was written.
+struct Probe {
+    reads: Vec<i64>,
+    written: Vec<i64>,
+}
+
+impl Device for Probe {
+    fn read(&mut self, offset: usize) -> i64 {
+        self.reads[offset] += 1;
+        self.reads[offset]
+    }
+
+    fn write(&mut self, offset: usize, value: i64) {
+        self.written[offset] = value;
+    }
+}
+
+#[test]
+fn reads_and_writes_go_to_the_device() {
+    // out @10; out @10; add @10, #5, @11; out [rb+11]; hlt
+    let mut image = vec![4, 10, 4, 10, 1001, 10, 5, 11, 204, 11, 99];
+    image.resize(12, 0);
+    let mut program = Program::init(image);
+    let probe = program.attach(10, 2, Probe { reads: vec![0, 0], written: vec![0, 0] });
+
+    assert_eq!(program.execute(&[]), Ok(vec![1, 2, 1]));
+    let probe = probe.lock().unwrap();
+    assert_eq!(probe.reads, [3, 1]);
+    assert_eq!(probe.written, [0, 8]);
+    // Memory under the device is untouched.
+    assert_eq!(program.memory[11], 0);
+}
+
+#[test]
+fn random_is_reproducible() {
+    // Outputs three reads of the device at 100.
+    let image: Vec<i64> = vec![4, 100, 4, 100, 4, 100, 99];
+    let run = |seed| {
+        let mut program = Program::init(image.clone());
+        program.attach(100, 1, Random::new(seed));
+        program.execute(&[]).unwrap()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+    assert!(run(7).iter().all(|&value| (0..1 << 31).contains(&value)));
+}
+
+#[test]
+fn detached_addresses_are_memory_again() {
+    let mut program = Program::init(vec![1101, 2, 3, 7, 4, 7, 99, 0]);
+    let screen = program.attach(7, 1, Framebuffer::new(1, 1));
+    assert!(program.clone().execute(&[]).is_ok());
+    assert_eq!(screen.lock().unwrap().pixels, [5]);
+
+    assert!(program.detach(7));
+    assert!(!program.detach(7));
+    assert_eq!(program.execute(&[]), Ok(vec![5]));
+    assert_eq!(program.memory[7], 5);
+}
+
+#[test]
+fn input_only_writes_the_device() {
+    let mut program = Program::init(vec![3, 10, 4, 10, 99, 0, 0, 0, 0, 0, 0]);
+    assert!(!program.is_limited());
+    let probe = program.attach(10, 1, Probe { reads: vec![0], written: vec![0] });
+    // Translated code leaves devices to the interpreter.
+    assert!(program.is_limited());
+    assert_eq!(program.execute(&[7]), Ok(vec![1]));
+    let probe = probe.lock().unwrap();
+    assert_eq!((probe.reads[0], probe.written[0]), (1, 7));
+}
@@ -0,0 +1,79 @@
+//! Custom instruction sets.
+
+use std::sync::{Arc, Mutex};
+
+use intcode::opcodes::{Opcodes, Outcome};
+use intcode::{IntcodeError, Program};
+
+#[test]
+fn standard_set_runs_as_usual() {
+    let image = intcode::parse::<i64>(include_str!("../../05/src/example")).unwrap();
+    let mut program = Program::init(image);
+    program.set_opcodes(Opcodes::standard());
+    assert_eq!(program.execute(&[8]), Ok(vec![1000]));
+}
+
+#[test]
+fn custom_instructions() {
+    let printed = Arc::new(Mutex::new(vec![]));
+    let log = printed.clone();
+    let mut opcodes: Opcodes = Opcodes::standard();
+    // DBG a: records a. NEG a, b: b = -a. SKZ a, b: jumps to b if a is 0,
+    // else outputs a.
+    opcodes
+        .register(10, "DBG", 1, 0, move |call| {
+            log.lock().unwrap().push(call.operands[0]);
+            Ok(Outcome::default())
+        })
+        .register(11, "NEG", 2, 0b10, |call| Ok(Outcome { write: Some(-call.operands[0]), ..Outcome::default() }))
+        .register(12, "SKZ", 2, 0, |call| match call.operands[0] {
+            0 => Ok(Outcome { jump: Some(call.operands[1] as usize), ..Outcome::default() }),
+            value => Ok(Outcome { output: Some(value), ..Outcome::default() }),
+        });
+
+    // in @20; neg @20, @21; dbg @21; skz @21, #0; hlt
+    let mut image = vec![3, 20, 11, 20, 21, 10, 21, 1012, 21, 0, 99];
+    image.resize(22, 0);
+    let mut program = Program::init(image);
+    program.set_opcodes(opcodes);
+    assert_eq!(program.execute(&[5]), Ok(vec![-5]));
+    assert_eq!(*printed.lock().unwrap(), [-5]);
+    assert_eq!(program.memory[21], -5);
+}
+
+#[test]
+fn removed_opcodes_are_unknown() {
+    // A sandbox without input.
+    let mut opcodes = Opcodes::standard();
+    opcodes.remove(3);
+    let mut program = Program::init(vec![3, 0, 99]);
+    program.set_opcodes(opcodes);
+    assert_eq!(program.execute(&[1]), Err(IntcodeError::UnknownOpcode { addr: 0, value: 3 }));
+}
+
+#[test]
+#[should_panic]
+fn at_most_one_write() {
+    Opcodes::<i64>::standard().register(10, "SWAP", 2, 0b11, |_| Ok(Outcome::default()));
+}
+
+#[test]
+fn handlers_with_state_are_not_loops() {
+    // TICK halts on its fifth call, so `tick; jmp 0` isn't an endless loop
+    // even though the machine comes back to the same state.
+    let calls = Arc::new(Mutex::new(0));
+    let count = calls.clone();
+    let mut opcodes: Opcodes = Opcodes::standard();
+    opcodes.register(10, "TICK", 0, 0, move |_| {
+        let mut calls = count.lock().unwrap();
+        *calls += 1;
+        Ok(Outcome { halt: *calls == 5, ..Outcome::default() })
+    });
+
+    let mut program = Program::init(vec![10, 1105, 1, 0]);
+    program.set_opcodes(opcodes);
+    program.set_loop_detection(true);
+    assert_eq!(program.execute(&[]), Ok(vec![]));
+    assert!(program.halted);
+    assert_eq!(*calls.lock().unwrap(), 5);
+}
@@ -0,0 +1,33 @@
+//! Carrying on in a wider word when an instruction overflows.
+
+use intcode::{IntcodeError, Program, Promoting};
+
+fn promoting(text: &str) -> Promoting {
+    Promoting::new(Program::init(intcode::parse::<i64>(text).unwrap()))
+}
+
+#[test]
+fn overflow_promotes_to_i128_then_bigint() {
+    // Squares 2^62 - 1 into 13, outputs it, squares that and outputs it.
+    let mut machine = promoting("1102,4611686018427387903,4611686018427387903,13,4,13,2,13,13,13,4,13,99,0");
+    let output = machine.execute(&[]).unwrap();
+    assert_eq!(output[0].to_string(), "21267647932558653957237540927630737409");
+    assert_eq!(output[1].to_string(), "452312848583266387981005301728519592439704926245031226053052705965112033281");
+    assert!(matches!(machine, Promoting::Big(_)));
+
+    let mut trapping = Program::init(intcode::parse::<i64>("1102,4611686018427387903,4611686018427387903,5,99,0").unwrap());
+    assert_eq!(trapping.execute(&[]), Err(IntcodeError::Overflow { addr: 0 }));
+}
+
+#[test]
+fn promotion_keeps_input_and_output() {
+    // Echoes its input, then multiplies it by 2^62 and outputs that.
+    let mut machine = promoting("3,13,4,13,1002,13,4611686018427387904,13,4,13,99,0,0,0");
+    let output = machine.execute(&[6]).unwrap();
+    assert_eq!(output.iter().map(ToString::to_string).collect::<Vec<_>>(), ["6", "27670116110564327424"]);
+    assert!(matches!(machine, Promoting::I128(_)));
+
+    let mut small = promoting("3,9,1002,9,2,9,4,9,99,0");
+    assert_eq!(small.execute(&[21]).unwrap()[0].to_string(), "42");
+    assert!(matches!(small, Promoting::I64(_)));
+}
@@ -0,0 +1,63 @@
+//! Recording I/O and replaying it.
+
+use intcode::load::LoadError;
+use intcode::record::{Divergence, Io, Recording};
+use intcode::Program;
+
+/// Outputs 999, 1000 or 1001 as the input is below, equal to or above 8.
+const COMPARE: &str = include_str!("../../05/src/example");
+
+fn record(text: &str, input: &[i64]) -> Recording {
+    let mut recording = Recording::new();
+    Program::init(intcode::parse::<i64>(text).unwrap()).execute_with(input, &mut recording).unwrap();
+    recording
+}
+
+#[test]
+fn replay_matches_and_survives_saving() {
+    let recording = record(COMPARE, &[9]);
+    assert_eq!(recording.inputs(), [9]);
+    assert_eq!(recording.entries.last().map(|entry| entry.io), Some(Io::Output(1001)));
+
+    let mut text = vec![];
+    recording.write(&mut text).unwrap();
+    let loaded = Recording::read(&text[..]).unwrap();
+    assert_eq!(loaded, recording);
+    assert_eq!(loaded.replay(&mut Program::init(intcode::parse::<i64>(COMPARE).unwrap())), Ok(()));
+}
+
+#[test]
+fn replay_reports_the_first_divergent_step() {
+    // Echoes its input, then its input plus one.
+    let recording = record("3,11,4,11,1001,11,1,11,4,11,99,0", &[5]);
+    assert_eq!(recording.steps, 5);
+
+    // Plus two instead.
+    let mut changed = Program::init(intcode::parse::<i64>("3,11,4,11,1001,11,2,11,4,11,99,0").unwrap());
+    assert_eq!(
+        recording.replay(&mut changed),
+        Err(Divergence::Io { step: 3, expected: Some(Io::Output(6)), found: Some(Io::Output(7)) })
+    );
+
+    // Halting before the second output.
+    let mut short = Program::init(intcode::parse::<i64>("3,11,4,11,1001,11,1,11,99,0,0,0").unwrap());
+    assert_eq!(recording.replay(&mut short), Err(Divergence::Io { step: 3, expected: Some(Io::Output(6)), found: None }));
+}
+
+#[test]
+fn halting_early_is_a_divergence() {
+    let recording = record("1101,1,1,7,1101,1,1,7,99", &[]);
+    assert_eq!(recording.steps, 3);
+    let mut halts = Program::init(vec![99]);
+    assert_eq!(recording.replay(&mut halts), Err(Divergence::Stopped { step: 1, recorded: 3 }));
+}
+
+#[test]
+fn malformed_recordings_are_rejected() {
+    assert!(Recording::read(&b"0 in 5\n1 sideways 3\n"[..]).is_err());
+    assert!(Recording::read(&b"0 in five\n"[..]).is_err());
+    match Recording::read(&b"0 in 5\nsteps -1\n"[..]) {
+        Err(LoadError::Parse(e)) => assert_eq!((e.line, e.reason.as_str()), (2, "negative step count")),
+        other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,55 @@
+//! Saving and restoring snapshots, and refusing malformed ones.
+
+use std::io;
+
+use intcode::{Program, Snapshot, PAGE_SIZE};
+
+fn varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// A snapshot header followed by `pages` page indices, each page all zeros.
+fn snapshot(len: u64, limit: u64, count: u64, pages: &[u64]) -> Vec<u8> {
+    let mut bytes = b"ICSN\x01\x00".to_vec();
+    for &value in &[0, 0, len, limit, count] {
+        varint(&mut bytes, value);
+    }
+    for &index in pages {
+        varint(&mut bytes, index);
+        bytes.extend(vec![0; PAGE_SIZE]);
+    }
+    bytes
+}
+
+#[test]
+fn snapshots_round_trip() {
+    let mut program = Program::init(intcode::parse::<i64>("109,2000,21101,3,4,0,99").unwrap());
+    program.execute(&[]).unwrap();
+    let mut bytes = vec![];
+    program.snapshot().write_to(&mut bytes).unwrap();
+
+    let restored = Program::from(Snapshot::read_from(&bytes[..]).unwrap());
+    assert_eq!(restored.memory.range(0, 2010), program.memory.range(0, 2010));
+    assert_eq!((restored.i_ptr, restored.relative_base, restored.halted), (6, 2000, true));
+}
+
+#[test]
+fn malformed_snapshots_are_rejected() {
+    let cases = [
+        snapshot(2048, 4096, 1, &[u64::MAX]),
+        snapshot(2048, 4096, 1, &[2]),
+        snapshot(2048, 4096, 3, &[0, 1, 1]),
+        snapshot(8192, 4096, 0, &[]),
+        snapshot(u64::MAX, u64::MAX, u64::MAX, &[]),
+        snapshot(1 << 50, 1 << 50, 1, &[(1 << 40) - 1]),
+    ];
+    for bytes in &cases {
+        let error = Snapshot::read_from(&bytes[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", error);
+    }
+    assert!(Snapshot::read_from(&snapshot(2048, 4096, 2, &[0, 1])[..]).is_ok());
+}
@@ -0,0 +1,51 @@
+//! Symbolic execution, checked against concrete runs of day 05's examples.
+
+use intcode::symbolic::{End, Explorer, Solution};
+use intcode::Program;
+
+/// Each program and the outputs it can give: 1 for input equal to (or
+/// below) 8 and 0 otherwise, or 999, 1000 and 1001 for the larger example.
+const COMPARE: [(&str, &[i64]); 5] = [
+    ("3,9,8,9,10,9,4,9,99,-1,8", &[0, 1]),
+    ("3,9,7,9,10,9,4,9,99,-1,8", &[0, 1]),
+    ("3,3,1108,-1,8,3,4,3,99", &[0, 1]),
+    ("3,3,1107,-1,8,3,4,3,99", &[0, 1]),
+    (include_str!("../../05/src/example"), &[999, 1000, 1001]),
+];
+
+#[test]
+fn solved_inputs_give_the_expected_output() {
+    for &(text, outputs) in &COMPARE {
+        let image = intcode::parse::<i64>(text).unwrap();
+        let paths = Explorer::default().explore(&image);
+        assert!(paths.iter().all(|path| path.end == End::Halted), "{}: {:?}", text, paths);
+
+        for &output in outputs {
+            let inputs = paths.iter()
+                .find_map(|path| match path.solve_outputs(&[output]) {
+                    Solution::Sat(inputs) => Some(inputs),
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("{}: nothing outputs {}", text, output));
+            assert_eq!(Program::init(image.clone()).execute(&inputs), Ok(vec![output]), "{} with {:?}", text, inputs);
+        }
+    }
+}
+
+#[test]
+fn impossible_outputs_are_unsat() {
+    for &(text, _) in &COMPARE[..4] {
+        let paths = Explorer::default().explore(&intcode::parse::<i64>(text).unwrap());
+        for path in &paths {
+            assert_eq!(path.solve_outputs(&[2]), Solution::Unsat, "{}: {}", text, path);
+        }
+    }
+}
+
+#[test]
+fn symbolic_addresses_are_unsupported() {
+    // Reads an address, then outputs whatever is there.
+    let paths = Explorer::default().explore(&intcode::parse::<i64>("3,3,4,0,99").unwrap());
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].end, End::Unsupported { addr: 2, reason: "symbolic address" });
+}